@@ -10,6 +10,10 @@ impl Chromosome {
         self.genes.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.genes.is_empty()
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &f32> {
         self.genes.iter()
     }
@@ -18,6 +22,14 @@ impl Chromosome {
         self.genes.iter_mut()
     }
 
+    pub fn as_slice(&self) -> &[f32] {
+        &self.genes
+    }
+
+    pub fn distance(&self, other: &Chromosome, method: &dyn DistanceMethod) -> f32 {
+        method.distance(&self.genes, &other.genes)
+    }
+
     pub fn split_at(&self, index: usize) -> [Chromosome; 2] {
         let (left, right) = self.genes.split_at(index);
         [
@@ -111,4 +123,21 @@ mod tests {
             assert_eq!(genes[2], 3.0);
         }
     }
+
+    mod distance {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let a = Chromosome {
+                genes: vec![1.0, 2.0, 3.0],
+            };
+            let b = Chromosome {
+                genes: vec![1.0, 2.5, 5.0],
+            };
+
+            assert_eq!(a.distance(&b, &HammingDistance::new(0.5)), 1.0 / 3.0);
+            assert_eq!(a.distance(&a, &EuclideanDistance::new()), 0.0);
+        }
+    }
 }
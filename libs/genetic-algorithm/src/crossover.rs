@@ -17,6 +17,12 @@ impl UniformCrossover {
     }
 }
 
+impl Default for UniformCrossover {
+    fn default() -> UniformCrossover {
+        UniformCrossover::new()
+    }
+}
+
 impl CrossoverMethod for UniformCrossover {
     fn crossover(
         &self,
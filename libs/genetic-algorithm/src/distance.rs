@@ -0,0 +1,185 @@
+/// Measures how far apart two sets of genes, or any other equally long
+/// descriptions of individuals such as their behaviour, are
+pub trait DistanceMethod {
+    fn distance(&self, a: &[f32], b: &[f32]) -> f32;
+
+    /// Mean distance between every pair of members, zero with fewer than two
+    fn diversity(&self, members: &[&[f32]]) -> f32 {
+        let mut total = 0.0;
+        let mut pairs = 0;
+
+        for (idx, a) in members.iter().enumerate() {
+            for b in &members[idx + 1..] {
+                total += self.distance(a, b);
+                pairs += 1;
+            }
+        }
+
+        if pairs == 0 {
+            0.0
+        } else {
+            total / pairs as f32
+        }
+    }
+}
+
+pub struct EuclideanDistance;
+
+impl EuclideanDistance {
+    pub fn new() -> EuclideanDistance {
+        EuclideanDistance
+    }
+}
+
+impl Default for EuclideanDistance {
+    fn default() -> EuclideanDistance {
+        EuclideanDistance::new()
+    }
+}
+
+impl DistanceMethod for EuclideanDistance {
+    fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
+        assert_eq!(a.len(), b.len());
+
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| (a - b).powi(2))
+            .sum::<f32>()
+            .sqrt()
+    }
+}
+
+/// One minus the cosine similarity, from 0 for genes pointing the same way
+/// to 2 for opposite ones, regardless of their magnitude
+pub struct CosineDistance;
+
+impl CosineDistance {
+    pub fn new() -> CosineDistance {
+        CosineDistance
+    }
+}
+
+impl Default for CosineDistance {
+    fn default() -> CosineDistance {
+        CosineDistance::new()
+    }
+}
+
+impl DistanceMethod for CosineDistance {
+    fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
+        assert_eq!(a.len(), b.len());
+
+        let dot: f32 = a.iter().zip(b).map(|(a, b)| a * b).sum();
+        let norm_a = a.iter().map(|a| a * a).sum::<f32>().sqrt();
+        let norm_b = b.iter().map(|b| b * b).sum::<f32>().sqrt();
+
+        // Zero vectors point nowhere, so they're only alike each other
+        if norm_a == 0.0 || norm_b == 0.0 {
+            return if norm_a == norm_b { 0.0 } else { 1.0 };
+        }
+
+        1.0 - (dot / (norm_a * norm_b)).clamp(-1.0, 1.0)
+    }
+}
+
+pub struct HammingDistance {
+    // Genes closer than this count as equal
+    tolerance: f32,
+}
+
+impl HammingDistance {
+    pub fn new(tolerance: f32) -> HammingDistance {
+        assert!(tolerance >= 0.0);
+
+        HammingDistance { tolerance }
+    }
+}
+
+/// Fraction of genes differing by more than the tolerance
+impl DistanceMethod for HammingDistance {
+    fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
+        assert_eq!(a.len(), b.len());
+
+        if a.is_empty() {
+            return 0.0;
+        }
+
+        let differing = a
+            .iter()
+            .zip(b)
+            .filter(|(a, b)| (*a - *b).abs() > self.tolerance)
+            .count();
+
+        differing as f32 / a.len() as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    mod euclidean {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let distance = EuclideanDistance::new().distance(&[1.0, 2.0], &[4.0, 6.0]);
+
+            assert_relative_eq!(distance, 5.0);
+        }
+    }
+
+    mod cosine {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let method = CosineDistance::new();
+            let same = method.distance(&[1.0, 2.0], &[2.0, 4.0]);
+            let orthogonal = method.distance(&[1.0, 0.0], &[0.0, 3.0]);
+            let opposite = method.distance(&[1.0, 1.0], &[-1.0, -1.0]);
+
+            assert_relative_eq!(same, 0.0, epsilon = 1e-6);
+            assert_relative_eq!(orthogonal, 1.0);
+            assert_relative_eq!(opposite, 2.0);
+        }
+
+        #[test]
+        fn test_zero() {
+            let method = CosineDistance::new();
+
+            assert_eq!(method.distance(&[0.0, 0.0], &[0.0, 0.0]), 0.0);
+            assert_eq!(method.distance(&[0.0, 0.0], &[1.0, 0.0]), 1.0);
+        }
+    }
+
+    mod hamming {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let distance =
+                HammingDistance::new(0.1).distance(&[1.0, 2.0, 3.0, 4.0], &[1.05, 2.5, 3.0, 3.0]);
+
+            assert_relative_eq!(distance, 0.5);
+        }
+    }
+
+    mod diversity {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let members: Vec<&[f32]> = vec![&[0.0, 0.0], &[3.0, 4.0], &[0.0, 0.0]];
+
+            // Pairwise distances of 5, 0 and 5
+            assert_relative_eq!(EuclideanDistance::new().diversity(&members), 10.0 / 3.0);
+        }
+
+        #[test]
+        fn test_single_member() {
+            assert_eq!(EuclideanDistance::new().diversity(&[&[1.0]]), 0.0);
+        }
+    }
+}
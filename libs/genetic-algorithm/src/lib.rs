@@ -3,11 +3,13 @@ use rand::Rng;
 use std::iter::FromIterator;
 use std::ops::Index;
 
-pub use self::{chromosome::*, crossover::*, mutation::*, selection::*};
+pub use self::{chromosome::*, crossover::*, distance::*, mutation::*, novelty::*, selection::*};
 
 mod chromosome;
 mod crossover;
+mod distance;
 mod mutation;
+mod novelty;
 mod selection;
 
 pub struct GeneticAlgorithm<S> {
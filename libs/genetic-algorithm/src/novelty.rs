@@ -0,0 +1,380 @@
+use crate::*;
+
+/// Individuals that can describe what they did, e.g. where they ended up or
+/// what they ate, so that novelty search can reward doing something new
+/// rather than something better. Descriptors of one population have to be
+/// equally long.
+pub trait Behaviour {
+    fn behaviour(&self) -> &[f32];
+}
+
+/// Scores individuals by how far their behaviour lies from that of their
+/// nearest neighbours, among the current population and an archive of past
+/// novel behaviours, which helps on deceptive problems where following the
+/// fitness leads into dead ends.
+pub struct NoveltySearch {
+    neighbours: usize,       // Nearest behaviours averaged into the novelty, k
+    archive_threshold: f32,  // Novelty past which a behaviour is archived
+    archive_capacity: usize, // Behaviours kept, the oldest being dropped first
+    novelty_weight: f32,     // 0 selects on fitness alone, 1 on novelty alone
+    distance_method: Box<dyn DistanceMethod>,
+    archive: Vec<Vec<f32>>,
+}
+
+/// Individual whose fitness is replaced by its score blending novelty with
+/// the original fitness, to be evolved by `GeneticAlgorithm::step`
+#[derive(Clone, Debug)]
+pub struct Novel<I> {
+    individual: I,
+    novelty: f32,
+    score: f32,
+}
+
+impl NoveltySearch {
+    pub fn new(
+        neighbours: usize,
+        archive_threshold: f32,
+        archive_capacity: usize,
+        novelty_weight: f32,
+        distance_method: impl DistanceMethod + 'static,
+    ) -> NoveltySearch {
+        assert!(neighbours > 0);
+        assert!((0.0..=1.0).contains(&novelty_weight));
+
+        NoveltySearch {
+            neighbours,
+            archive_threshold,
+            archive_capacity,
+            novelty_weight,
+            distance_method: Box::new(distance_method),
+            archive: Vec::new(),
+        }
+    }
+
+    pub fn archive(&self) -> &[Vec<f32>] {
+        &self.archive
+    }
+
+    /// Mean distance from each behaviour to its nearest neighbours among the
+    /// other `behaviours` and the archive
+    pub fn novelty(&self, behaviours: &[&[f32]]) -> Vec<f32> {
+        behaviours
+            .iter()
+            .enumerate()
+            .map(|(idx, behaviour)| {
+                let mut distances: Vec<_> = behaviours
+                    .iter()
+                    .enumerate()
+                    .filter(|(other_idx, _)| *other_idx != idx)
+                    .map(|(_, other)| *other)
+                    .chain(self.archive.iter().map(Vec::as_slice))
+                    .map(|other| self.distance_method.distance(behaviour, other))
+                    .collect();
+
+                if distances.is_empty() {
+                    return 0.0;
+                }
+
+                let k = self.neighbours.min(distances.len());
+                distances.select_nth_unstable_by(k - 1, |a, b| a.total_cmp(b));
+                distances[..k].iter().sum::<f32>() / k as f32
+            })
+            .collect()
+    }
+
+    /// Scores the population, archiving behaviours novel enough to be worth
+    /// remembering. Fitness and novelty are each scaled to 0..=1 between
+    /// their minimum and maximum in the population before being blended, so
+    /// negative fitness works too.
+    pub fn evaluate<I>(&mut self, population: Vec<I>) -> Vec<Novel<I>>
+    where
+        I: Individual + Behaviour,
+    {
+        let novelty = {
+            let behaviours: Vec<_> = population.iter().map(Behaviour::behaviour).collect();
+            self.novelty(&behaviours)
+        };
+
+        for (individual, novelty) in population.iter().zip(&novelty) {
+            if *novelty > self.archive_threshold {
+                self.archive.push(individual.behaviour().to_vec());
+            }
+        }
+
+        let overflow = self.archive.len().saturating_sub(self.archive_capacity);
+        self.archive.drain(..overflow);
+
+        let fitness_range = range(population.iter().map(Individual::fitness));
+        let novelty_range = range(novelty.iter().copied());
+
+        population
+            .into_iter()
+            .zip(novelty)
+            .map(|(individual, novelty)| {
+                let score = (1.0 - self.novelty_weight)
+                    * normalize(individual.fitness(), fitness_range)
+                    + self.novelty_weight * normalize(novelty, novelty_range);
+
+                Novel {
+                    individual,
+                    novelty,
+                    score,
+                }
+            })
+            .collect()
+    }
+}
+
+impl<I> Novel<I> {
+    pub fn individual(&self) -> &I {
+        &self.individual
+    }
+
+    pub fn into_individual(self) -> I {
+        self.individual
+    }
+
+    pub fn novelty(&self) -> f32 {
+        self.novelty
+    }
+}
+
+impl<I> Individual for Novel<I>
+where
+    I: Individual,
+{
+    fn fitness(&self) -> f32 {
+        self.score
+    }
+
+    fn chromosome(&self) -> &Chromosome {
+        self.individual.chromosome()
+    }
+
+    /// Offspring are yet to be evaluated
+    fn create(chromosome: Chromosome) -> Novel<I> {
+        Novel {
+            individual: I::create(chromosome),
+            novelty: 0.0,
+            score: 0.0,
+        }
+    }
+}
+
+/// Minimum and maximum of the values
+fn range(values: impl Iterator<Item = f32>) -> (f32, f32) {
+    values.fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| {
+        (min.min(value), max.max(value))
+    })
+}
+
+fn normalize(value: f32, (min, max): (f32, f32)) -> f32 {
+    if max > min {
+        (value - min) / (max - min)
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[derive(Clone, Debug)]
+    struct Walker {
+        fitness: f32,
+        chromosome: Chromosome,
+        position: [f32; 2],
+    }
+
+    impl Walker {
+        fn new(fitness: f32, x: f32, y: f32) -> Walker {
+            Walker {
+                fitness,
+                chromosome: vec![x, y].into_iter().collect(),
+                position: [x, y],
+            }
+        }
+    }
+
+    impl Individual for Walker {
+        fn fitness(&self) -> f32 {
+            self.fitness
+        }
+
+        fn chromosome(&self) -> &Chromosome {
+            &self.chromosome
+        }
+
+        fn create(chromosome: Chromosome) -> Walker {
+            let position = [chromosome[0], chromosome[1]];
+
+            Walker {
+                fitness: 0.0,
+                chromosome,
+                position,
+            }
+        }
+    }
+
+    impl Behaviour for Walker {
+        fn behaviour(&self) -> &[f32] {
+            &self.position
+        }
+    }
+
+    fn search(novelty_weight: f32) -> NoveltySearch {
+        NoveltySearch::new(2, 5.0, 3, novelty_weight, EuclideanDistance::new())
+    }
+
+    mod novelty {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let behaviours: [&[f32]; 4] = [&[0.0], &[1.0], &[3.0], &[10.0]];
+            let novelty = search(1.0).novelty(&behaviours);
+
+            assert_relative_eq!(novelty.as_slice(), [2.0, 1.5, 2.5, 8.0].as_slice());
+        }
+
+        #[test]
+        fn test_archive() {
+            let mut search = search(1.0);
+            search.archive = vec![vec![2.0]];
+
+            let behaviours: [&[f32]; 2] = [&[0.0], &[10.0]];
+            let novelty = search.novelty(&behaviours);
+
+            assert_relative_eq!(novelty.as_slice(), [6.0, 9.0].as_slice());
+        }
+
+        #[test]
+        fn test_alone() {
+            let behaviours: [&[f32]; 1] = [&[1.0]];
+
+            assert_eq!(search(1.0).novelty(&behaviours), vec![0.0]);
+        }
+    }
+
+    mod evaluate {
+        use super::*;
+
+        fn population() -> Vec<Walker> {
+            vec![
+                Walker::new(4.0, 0.0, 0.0),
+                Walker::new(2.0, 1.0, 0.0),
+                Walker::new(1.0, 0.0, 1.0),
+                Walker::new(0.0, 20.0, 0.0),
+            ]
+        }
+
+        #[test]
+        fn test_fitness() {
+            let scores: Vec<_> = search(0.0)
+                .evaluate(population())
+                .iter()
+                .map(Individual::fitness)
+                .collect();
+
+            assert_relative_eq!(scores.as_slice(), [1.0, 0.5, 0.25, 0.0].as_slice());
+        }
+
+        #[test]
+        fn test_negative_fitness() {
+            let scores: Vec<_> = search(0.0)
+                .evaluate(vec![
+                    Walker::new(-4.0, 0.0, 0.0),
+                    Walker::new(-2.0, 1.0, 0.0),
+                    Walker::new(-3.0, 0.0, 1.0),
+                ])
+                .iter()
+                .map(Individual::fitness)
+                .collect();
+
+            assert_relative_eq!(scores.as_slice(), [0.0, 1.0, 0.5].as_slice());
+        }
+
+        #[test]
+        fn test_novelty() {
+            let novel = search(1.0).evaluate(population());
+
+            // The walker that went furthest away is the most novel
+            assert_relative_eq!(novel[3].fitness(), 1.0);
+            assert!(novel[..3].iter().all(|novel| novel.fitness() < 0.2));
+            assert_eq!(novel[3].individual().fitness(), 0.0);
+        }
+
+        #[test]
+        fn test_archive() {
+            let mut search = search(0.5);
+            let _ = search.evaluate(population());
+
+            assert_eq!(search.archive(), &[vec![20.0, 0.0]]);
+
+            // Archived behaviours count as neighbours, making nearby ones less novel
+            let novel = search.evaluate(vec![
+                Walker::new(0.0, 20.0, 1.0),
+                Walker::new(0.0, 0.0, 0.0),
+            ]);
+            assert_relative_eq!(novel[0].novelty(), (1.0 + 20.0_f32.hypot(1.0)) / 2.0);
+        }
+
+        #[test]
+        fn test_capacity() {
+            let mut search = search(1.0);
+
+            for step in 0..5 {
+                let offset = 100.0 * step as f32;
+                let _ = search.evaluate(vec![
+                    Walker::new(0.0, offset, 0.0),
+                    Walker::new(0.0, offset + 50.0, 0.0),
+                ]);
+            }
+
+            // Only the latest behaviours are kept
+            assert_eq!(search.archive().len(), 3);
+            assert_eq!(search.archive()[2], vec![450.0, 0.0]);
+        }
+    }
+
+    mod genetic_algorithm {
+        use super::*;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut search = search(1.0);
+            let ga = GeneticAlgorithm::new(
+                RouletteWheelSelection::new(),
+                UniformCrossover::new(),
+                GaussianMutation::new(0.5, 1.0),
+            );
+
+            let mut population: Vec<_> = (0..10)
+                .map(|n| Walker::new(1.0, 0.01 * n as f32, 0.0))
+                .collect();
+
+            for _ in 0..20 {
+                let novel = search.evaluate(population);
+                population = ga
+                    .step(&mut rng, &novel)
+                    .into_iter()
+                    .map(Novel::into_individual)
+                    .collect();
+            }
+
+            // Without any fitness to follow, walkers still spread out
+            let spread = EuclideanDistance::new().diversity(
+                &population
+                    .iter()
+                    .map(Behaviour::behaviour)
+                    .collect::<Vec<_>>(),
+            );
+            assert!(spread > 1.0, "{}", spread);
+        }
+    }
+}
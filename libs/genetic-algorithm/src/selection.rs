@@ -14,6 +14,12 @@ impl RouletteWheelSelection {
     }
 }
 
+impl Default for RouletteWheelSelection {
+    fn default() -> RouletteWheelSelection {
+        RouletteWheelSelection::new()
+    }
+}
+
 impl SelectionMethod for RouletteWheelSelection {
     fn select<'a, I>(&self, rng: &mut dyn rand::RngCore, population: &'a [I]) -> &'a I
     where
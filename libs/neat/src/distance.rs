@@ -0,0 +1,154 @@
+use crate::*;
+
+/// Coefficients of the compatibility distance between two genomes,
+/// δ = excess·E/N + disjoint·D/N + weight·W̄, used to group genomes into species
+#[derive(Debug, Clone, Copy)]
+pub struct CompatibilityDistance {
+    excess: f32,
+    disjoint: f32,
+    weight: f32,
+}
+
+impl CompatibilityDistance {
+    pub fn new(excess: f32, disjoint: f32, weight: f32) -> CompatibilityDistance {
+        CompatibilityDistance {
+            excess,
+            disjoint,
+            weight,
+        }
+    }
+
+    pub fn distance(&self, genome_a: &Genome, genome_b: &Genome) -> f32 {
+        let last_a = genome_a.connections.last().map(|gene| gene.innovation);
+        let last_b = genome_b.connections.last().map(|gene| gene.innovation);
+        let (last_a, last_b) = match (last_a, last_b) {
+            (Some(last_a), Some(last_b)) => (last_a, last_b),
+            // Every gene of the non-empty genome is excess
+            _ => {
+                let genes = genome_a.connections.len() + genome_b.connections.len();
+                return self.excess * genes as f32 / Self::normalizer(genes);
+            }
+        };
+
+        let mut excess = 0;
+        let mut disjoint = 0;
+        let mut matching = 0;
+        let mut weight_difference = 0.0;
+
+        for gene in &genome_a.connections {
+            match genome_b.connection(gene.innovation) {
+                Some(other) => {
+                    matching += 1;
+                    weight_difference += (gene.weight - other.weight).abs();
+                }
+                None if gene.innovation > last_b => excess += 1,
+                None => disjoint += 1,
+            }
+        }
+
+        for gene in &genome_b.connections {
+            match genome_a.connection(gene.innovation) {
+                Some(_) => {}
+                None if gene.innovation > last_a => excess += 1,
+                None => disjoint += 1,
+            }
+        }
+
+        let genes = genome_a.connections.len().max(genome_b.connections.len());
+        let normalizer = Self::normalizer(genes);
+        let mean_weight_difference = if matching > 0 {
+            weight_difference / matching as f32
+        } else {
+            0.0
+        };
+
+        self.excess * excess as f32 / normalizer
+            + self.disjoint * disjoint as f32 / normalizer
+            + self.weight * mean_weight_difference
+    }
+
+    /// Small genomes aren't normalized, as suggested by the original NEAT paper
+    fn normalizer(genes: usize) -> f32 {
+        if genes < 20 {
+            1.0
+        } else {
+            genes as f32
+        }
+    }
+}
+
+impl Default for CompatibilityDistance {
+    fn default() -> CompatibilityDistance {
+        CompatibilityDistance::new(1.0, 1.0, 0.4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn genome(genes: &[(usize, f32)]) -> Genome {
+        Genome {
+            inputs: 1,
+            outputs: 1,
+            nodes: vec![],
+            connections: genes
+                .iter()
+                .map(|(innovation, weight)| ConnectionGene {
+                    innovation: *innovation,
+                    from: 0,
+                    to: 1,
+                    weight: *weight,
+                    enabled: true,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test() {
+        let genome_a = genome(&[(0, 0.5), (1, 1.0), (3, 0.0)]);
+        let genome_b = genome(&[(0, 0.0), (2, 1.0), (3, 1.0), (5, 0.0), (6, 0.0)]);
+        let distance = CompatibilityDistance::new(1.0, 2.0, 4.0);
+
+        // Disjoint: 1 and 2, excess: 5 and 6, mean weight difference of 0 and 3: 0.75
+        assert_relative_eq!(distance.distance(&genome_a, &genome_b), 2.0 + 4.0 + 3.0);
+        assert_relative_eq!(distance.distance(&genome_b, &genome_a), 2.0 + 4.0 + 3.0);
+    }
+
+    #[test]
+    fn test_identical_genomes() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let genome = Genome::minimal(&mut rng, 5, 5);
+
+        assert_relative_eq!(
+            CompatibilityDistance::default().distance(&genome, &genome),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_empty_genome() {
+        let distance = CompatibilityDistance::new(1.0, 2.0, 4.0);
+
+        assert_relative_eq!(
+            distance.distance(&genome(&[]), &genome(&[(0, 1.0), (1, 1.0)])),
+            2.0
+        );
+    }
+
+    #[test]
+    fn test_normalizes_large_genomes() {
+        let genes: Vec<_> = (0..40).map(|innovation| (innovation, 0.0)).collect();
+        let distance = CompatibilityDistance::new(1.0, 1.0, 0.0);
+
+        // Half of the genes are excess
+        assert_relative_eq!(
+            distance.distance(&genome(&genes[..20]), &genome(&genes)),
+            0.5
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use crate::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Input,
+    Output,
+    Hidden,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeGene {
+    pub(crate) id: usize,
+    pub(crate) kind: NodeKind,
+    pub(crate) bias: f32,                  // Unused by input nodes
+    pub(crate) activation: nn::Activation, // Unused by input nodes
+}
+
+impl NodeGene {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    pub fn bias(&self) -> f32 {
+        self.bias
+    }
+
+    pub fn activation(&self) -> nn::Activation {
+        self.activation
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionGene {
+    pub(crate) innovation: usize, // Historical marking shared by every genome with this connection
+    pub(crate) from: usize,
+    pub(crate) to: usize,
+    pub(crate) weight: f32,
+    pub(crate) enabled: bool,
+}
+
+impl ConnectionGene {
+    pub fn innovation(&self) -> usize {
+        self.innovation
+    }
+
+    pub fn from(&self) -> usize {
+        self.from
+    }
+
+    pub fn to(&self) -> usize {
+        self.to
+    }
+
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+}
@@ -0,0 +1,485 @@
+use crate::*;
+
+/// Node ids start with the inputs, followed by the outputs and then hidden nodes
+#[derive(Debug, Clone)]
+pub struct Genome {
+    pub(crate) inputs: usize,
+    pub(crate) outputs: usize,
+    pub(crate) nodes: Vec<NodeGene>,             // Sorted by id
+    pub(crate) connections: Vec<ConnectionGene>, // Sorted by innovation
+}
+
+impl Genome {
+    /// Every input connected to every output, with random weights
+    pub fn minimal(rng: &mut dyn rand::RngCore, inputs: usize, outputs: usize) -> Genome {
+        let nodes = (0..inputs + outputs)
+            .map(|id| NodeGene {
+                id,
+                kind: if id < inputs {
+                    NodeKind::Input
+                } else {
+                    NodeKind::Output
+                },
+                bias: if id < inputs {
+                    0.0
+                } else {
+                    rng.gen_range(-1.0..=1.0)
+                },
+                activation: nn::Activation::Tanh,
+            })
+            .collect();
+
+        // Numbered in the same order as `InnovationHistory::new`
+        let connections = (0..inputs)
+            .flat_map(|from| (inputs..inputs + outputs).map(move |to| (from, to)))
+            .enumerate()
+            .map(|(innovation, (from, to))| ConnectionGene {
+                innovation,
+                from,
+                to,
+                weight: rng.gen_range(-1.0..=1.0),
+                enabled: true,
+            })
+            .collect();
+
+        Genome {
+            inputs,
+            outputs,
+            nodes,
+            connections,
+        }
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.outputs
+    }
+
+    pub fn nodes(&self) -> &[NodeGene] {
+        &self.nodes
+    }
+
+    pub fn connections(&self) -> &[ConnectionGene] {
+        &self.connections
+    }
+
+    /// Number of weights and biases used by the phenotype
+    pub fn parameters(&self) -> usize {
+        let weights = self.connections.iter().filter(|gene| gene.enabled).count();
+        let biases = self.nodes.len() - self.inputs;
+
+        weights + biases
+    }
+
+    /// Historical marking crossover: matching genes are inherited from either
+    /// parent at random, disjoint and excess genes from the fitter parent only
+    pub fn crossover(
+        rng: &mut dyn rand::RngCore,
+        parent_a: &Genome,
+        fitness_a: f32,
+        parent_b: &Genome,
+        fitness_b: f32,
+    ) -> Genome {
+        assert_eq!(parent_a.inputs, parent_b.inputs);
+        assert_eq!(parent_a.outputs, parent_b.outputs);
+
+        let (fitter, other) = if fitness_b > fitness_a {
+            (parent_b, parent_a)
+        } else {
+            (parent_a, parent_b)
+        };
+
+        let connections = fitter
+            .connections
+            .iter()
+            .map(|gene| match other.connection(gene.innovation) {
+                Some(matching) => {
+                    let mut child = if rng.gen_bool(0.5) {
+                        gene.clone()
+                    } else {
+                        matching.clone()
+                    };
+
+                    // A gene disabled in either parent usually stays disabled
+                    child.enabled = (gene.enabled && matching.enabled) || rng.gen_bool(0.25);
+                    child
+                }
+                None => gene.clone(),
+            })
+            .collect();
+
+        let nodes = fitter
+            .nodes
+            .iter()
+            .map(|node| match other.node(node.id) {
+                Some(matching) if rng.gen_bool(0.5) => matching.clone(),
+                _ => node.clone(),
+            })
+            .collect();
+
+        Genome {
+            inputs: fitter.inputs,
+            outputs: fitter.outputs,
+            nodes,
+            connections,
+        }
+    }
+
+    pub(crate) fn connection(&self, innovation: usize) -> Option<&ConnectionGene> {
+        self.connections
+            .binary_search_by_key(&innovation, |gene| gene.innovation)
+            .ok()
+            .map(|idx| &self.connections[idx])
+    }
+
+    pub(crate) fn node(&self, id: usize) -> Option<&NodeGene> {
+        self.nodes
+            .binary_search_by_key(&id, |node| node.id)
+            .ok()
+            .map(|idx| &self.nodes[idx])
+    }
+
+    /// Nudges every weight, bias and activation parameter with probability `rate`
+    pub(crate) fn mutate_weights(&mut self, rng: &mut dyn rand::RngCore, rate: f32, strength: f32) {
+        let weights = self.connections.iter_mut().map(|gene| &mut gene.weight);
+        let biases = self
+            .nodes
+            .iter_mut()
+            .filter(|node| node.kind != NodeKind::Input)
+            .flat_map(|node| once(&mut node.bias).chain(node.activation.parameter_mut()));
+
+        for value in weights.chain(biases) {
+            let sign = if rng.gen_bool(0.5) { -1.0 } else { 1.0 };
+
+            if rng.gen_bool(rate as _) {
+                *value += sign * strength * rng.gen::<f32>();
+            }
+        }
+    }
+
+    /// Connects two previously unconnected nodes, without creating cycles.
+    /// Returns false if the genome is already fully connected.
+    pub(crate) fn add_connection(
+        &mut self,
+        rng: &mut dyn rand::RngCore,
+        history: &mut InnovationHistory,
+    ) -> bool {
+        let candidates: Vec<_> = self
+            .nodes
+            .iter()
+            .flat_map(|from| self.nodes.iter().map(move |to| (from, to)))
+            .filter(|(from, to)| {
+                to.kind != NodeKind::Input
+                    && from.id != to.id
+                    && !self
+                        .connections
+                        .iter()
+                        .any(|gene| gene.from == from.id && gene.to == to.id)
+                    && !self.reaches(to.id, from.id)
+            })
+            .map(|(from, to)| (from.id, to.id))
+            .collect();
+
+        let (from, to) = match candidates.choose(rng) {
+            Some(candidate) => *candidate,
+            None => return false,
+        };
+
+        self.insert_connection(ConnectionGene {
+            innovation: history.connection(from, to),
+            from,
+            to,
+            weight: rng.gen_range(-1.0..=1.0),
+            enabled: true,
+        });
+
+        true
+    }
+
+    /// Splits an enabled connection in two with a new hidden node, preserving
+    /// its behaviour as closely as possible. Returns false if none can be split.
+    pub(crate) fn add_node(
+        &mut self,
+        rng: &mut dyn rand::RngCore,
+        history: &mut InnovationHistory,
+    ) -> bool {
+        let candidates: Vec<_> = self
+            .connections
+            .iter()
+            .enumerate()
+            .filter(|(_, gene)| gene.enabled)
+            .filter(|(_, gene)| {
+                // The same split can't be applied twice to one genome
+                match history.existing_split(gene.innovation) {
+                    Some(id) => self.node(id).is_none(),
+                    None => true,
+                }
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let idx = match candidates.choose(rng) {
+            Some(idx) => *idx,
+            None => return false,
+        };
+
+        self.connections[idx].enabled = false;
+        let split = self.connections[idx].clone();
+        let id = history.split(split.innovation);
+
+        self.insert_node(NodeGene {
+            id,
+            kind: NodeKind::Hidden,
+            bias: 0.0,
+            activation: nn::Activation::Tanh,
+        });
+        self.insert_connection(ConnectionGene {
+            innovation: history.connection(split.from, id),
+            from: split.from,
+            to: id,
+            weight: 1.0,
+            enabled: true,
+        });
+        self.insert_connection(ConnectionGene {
+            innovation: history.connection(id, split.to),
+            from: id,
+            to: split.to,
+            weight: split.weight,
+            enabled: true,
+        });
+
+        true
+    }
+
+    /// Gives a random hidden node an activation picked from `activations`.
+    /// Returns false if there are no hidden nodes or activations to pick from.
+    pub(crate) fn change_activation(
+        &mut self,
+        rng: &mut dyn rand::RngCore,
+        activations: &[nn::Activation],
+    ) -> bool {
+        let hidden: Vec<_> = (0..self.nodes.len())
+            .filter(|idx| self.nodes[*idx].kind == NodeKind::Hidden)
+            .collect();
+
+        match (hidden.choose(rng), activations.choose(rng)) {
+            (Some(idx), Some(activation)) => {
+                self.nodes[*idx].activation = *activation;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Flips a random connection between enabled and disabled
+    pub(crate) fn toggle_connection(&mut self, rng: &mut dyn rand::RngCore) -> bool {
+        match self.connections.choose_mut(rng) {
+            Some(gene) => {
+                gene.enabled = !gene.enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether `to` can be reached from `from`, following disabled connections
+    /// too since they might be re-enabled later
+    fn reaches(&self, from: usize, to: usize) -> bool {
+        let mut stack = vec![from];
+        let mut visited = vec![from];
+
+        while let Some(node) = stack.pop() {
+            if node == to {
+                return true;
+            }
+
+            for gene in self.connections.iter().filter(|gene| gene.from == node) {
+                if !visited.contains(&gene.to) {
+                    visited.push(gene.to);
+                    stack.push(gene.to);
+                }
+            }
+        }
+
+        false
+    }
+
+    fn insert_connection(&mut self, gene: ConnectionGene) {
+        let idx = self
+            .connections
+            .partition_point(|other| other.innovation < gene.innovation);
+        self.connections.insert(idx, gene);
+    }
+
+    fn insert_node(&mut self, node: NodeGene) {
+        let idx = self.nodes.partition_point(|other| other.id < node.id);
+        self.nodes.insert(idx, node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn innovations(genome: &Genome) -> Vec<usize> {
+        genome
+            .connections
+            .iter()
+            .map(|gene| gene.innovation)
+            .collect()
+    }
+
+    mod minimal {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let genome = Genome::minimal(&mut rng, 3, 2);
+
+            assert_eq!(genome.nodes.len(), 5);
+            assert_eq!(genome.nodes[2].kind, NodeKind::Input);
+            assert_eq!(genome.nodes[3].kind, NodeKind::Output);
+            assert_eq!(innovations(&genome), vec![0, 1, 2, 3, 4, 5]);
+            assert_eq!(genome.parameters(), 6 + 2);
+        }
+
+        #[test]
+        fn test_matches_history() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let genome = Genome::minimal(&mut rng, 3, 2);
+            let mut history = InnovationHistory::new(3, 2);
+
+            for gene in &genome.connections {
+                assert_eq!(history.connection(gene.from, gene.to), gene.innovation);
+            }
+        }
+    }
+
+    mod add_node {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut history = InnovationHistory::new(1, 1);
+            let mut genome = Genome::minimal(&mut rng, 1, 1);
+            let weight = genome.connections[0].weight;
+
+            assert!(genome.add_node(&mut rng, &mut history));
+
+            assert_eq!(genome.nodes.len(), 3);
+            assert_eq!(genome.nodes[2].kind, NodeKind::Hidden);
+            assert_eq!(innovations(&genome), vec![0, 1, 2]);
+            assert!(!genome.connections[0].enabled);
+            assert_eq!(
+                (genome.connections[1].from, genome.connections[1].to),
+                (0, 2)
+            );
+            assert_eq!(genome.connections[1].weight, 1.0);
+            assert_eq!(
+                (genome.connections[2].from, genome.connections[2].to),
+                (2, 1)
+            );
+            assert_eq!(genome.connections[2].weight, weight);
+        }
+
+        #[test]
+        fn test_same_split_shares_innovations() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut history = InnovationHistory::new(1, 1);
+            let mut genome_a = Genome::minimal(&mut rng, 1, 1);
+            let mut genome_b = Genome::minimal(&mut rng, 1, 1);
+
+            genome_a.add_node(&mut rng, &mut history);
+            genome_b.add_node(&mut rng, &mut history);
+
+            assert_eq!(innovations(&genome_a), innovations(&genome_b));
+            assert_eq!(genome_a.nodes[2].id, genome_b.nodes[2].id);
+        }
+    }
+
+    mod mutate_weights {
+        use super::*;
+
+        #[test]
+        fn test_evolves_prelu_slopes() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut genome = Genome::minimal(&mut rng, 2, 2);
+            genome.nodes[2].activation = nn::Activation::PReLU(0.25);
+
+            genome.mutate_weights(&mut rng, 1.0, 0.5);
+
+            assert_ne!(genome.nodes[2].activation, nn::Activation::PReLU(0.25));
+            assert!(matches!(
+                genome.nodes[2].activation,
+                nn::Activation::PReLU(slope) if (slope - 0.25).abs() <= 0.5
+            ));
+            assert_eq!(genome.nodes[3].activation, nn::Activation::Tanh);
+        }
+    }
+
+    mod add_connection {
+        use super::*;
+
+        #[test]
+        fn test_never_creates_cycles() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut history = InnovationHistory::new(2, 2);
+            let mut genome = Genome::minimal(&mut rng, 2, 2);
+
+            for _ in 0..5 {
+                genome.add_node(&mut rng, &mut history);
+            }
+            while genome.add_connection(&mut rng, &mut history) {}
+
+            for gene in &genome.connections {
+                assert!(!genome.reaches(gene.to, gene.from));
+                assert_ne!(genome.node(gene.to).unwrap().kind, NodeKind::Input);
+            }
+        }
+    }
+
+    mod crossover {
+        use super::*;
+
+        #[test]
+        fn test_inherits_structure_from_fitter_parent() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut history = InnovationHistory::new(2, 1);
+            let parent_a = Genome::minimal(&mut rng, 2, 1);
+            let mut parent_b = parent_a.clone();
+            parent_b.add_node(&mut rng, &mut history);
+
+            let child = Genome::crossover(&mut rng, &parent_a, 1.0, &parent_b, 2.0);
+            assert_eq!(innovations(&child), innovations(&parent_b));
+            assert_eq!(child.nodes.len(), parent_b.nodes.len());
+
+            let child = Genome::crossover(&mut rng, &parent_a, 2.0, &parent_b, 1.0);
+            assert_eq!(innovations(&child), innovations(&parent_a));
+            assert_eq!(child.nodes.len(), parent_a.nodes.len());
+        }
+
+        #[test]
+        fn test_mixes_matching_genes() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let parent_a = Genome::minimal(&mut rng, 10, 10);
+            let parent_b = Genome::minimal(&mut rng, 10, 10);
+
+            let child = Genome::crossover(&mut rng, &parent_a, 1.0, &parent_b, 1.0);
+            let from_a = child
+                .connections
+                .iter()
+                .zip(&parent_a.connections)
+                .filter(|(child, parent)| child.weight == parent.weight)
+                .count();
+
+            assert!(from_a > 25 && from_a < 75);
+        }
+    }
+}
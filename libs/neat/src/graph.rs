@@ -0,0 +1,164 @@
+use crate::*;
+
+impl Genome {
+    /// Genome node indices in each column of the drawing: the inputs, then
+    /// hidden nodes one column past the deepest node feeding them, and
+    /// finally the outputs
+    fn columns(&self) -> Vec<Vec<usize>> {
+        let idx_of: HashMap<_, _> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| (node.id, idx))
+            .collect();
+        let enabled: Vec<_> = self
+            .connections
+            .iter()
+            .filter(|gene| gene.enabled)
+            .map(|gene| (idx_of[&gene.from], idx_of[&gene.to]))
+            .collect();
+
+        // Longest path from the inputs, which settles since genomes are acyclic
+        let mut depths = vec![0; self.nodes.len()];
+        for _ in 0..self.nodes.len() {
+            let mut changed = false;
+
+            for (from, to) in &enabled {
+                if depths[*from] + 1 > depths[*to] {
+                    depths[*to] = depths[*from] + 1;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        let hidden = |idx: &usize| self.nodes[*idx].kind == NodeKind::Hidden;
+        let last = (0..self.nodes.len())
+            .filter(hidden)
+            .map(|idx| depths[idx].max(1) + 1)
+            .max()
+            .unwrap_or(1);
+
+        let mut columns = vec![Vec::new(); last + 1];
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let column = match node.kind {
+                NodeKind::Input => 0,
+                NodeKind::Hidden => depths[idx].max(1),
+                NodeKind::Output => last,
+            };
+
+            columns[column].push(idx);
+        }
+
+        columns
+    }
+}
+
+/// Hidden nodes are labelled with their activation, and only enabled
+/// connections are drawn
+impl nn::Graph for Genome {
+    fn layers(&self) -> Vec<nn::GraphLayer> {
+        let columns = self.columns();
+        let last = columns.len() - 1;
+
+        columns
+            .iter()
+            .enumerate()
+            .map(|(column, nodes)| nn::GraphLayer {
+                label: if column == 0 {
+                    "inputs".to_string()
+                } else if column == last {
+                    "outputs".to_string()
+                } else {
+                    format!("hidden {}", column)
+                },
+                nodes: nodes
+                    .iter()
+                    .map(|idx| match self.nodes[*idx].kind {
+                        NodeKind::Input => idx.to_string(),
+                        _ => self.nodes[*idx].activation.to_string(),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    fn edges(&self) -> Vec<nn::GraphEdge> {
+        // Position of each genome node in the drawing, keyed by its id
+        let position: HashMap<_, _> = self
+            .columns()
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .map(|(position, idx)| (self.nodes[idx].id, position))
+            .collect();
+
+        self.connections
+            .iter()
+            .filter(|gene| gene.enabled)
+            .map(|gene| nn::GraphEdge {
+                from: position[&gene.from],
+                to: position[&gene.to],
+                weight: gene.weight,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nn::Graph;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_minimal() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let genome = Genome::minimal(&mut rng, 2, 1);
+
+        let layers = genome.layers();
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[0].nodes, vec!["0", "1"]);
+        assert_eq!(layers[1].label, "outputs");
+        assert_eq!(layers[1].nodes, vec!["tanh"]);
+
+        let edges: Vec<_> = genome
+            .edges()
+            .iter()
+            .map(|edge| (edge.from, edge.to))
+            .collect();
+        assert_eq!(edges, vec![(0, 2), (1, 2)]);
+    }
+
+    #[test]
+    fn test_hidden_nodes() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut history = InnovationHistory::new(2, 1);
+        let mut genome = Genome::minimal(&mut rng, 2, 1);
+        genome.add_node(&mut rng, &mut history);
+        genome.add_node(&mut rng, &mut history);
+
+        let layers = genome.layers();
+        let sizes: Vec<_> = layers.iter().map(|layer| layer.nodes.len()).collect();
+        assert_eq!(sizes.iter().sum::<usize>(), genome.nodes.len());
+        assert_eq!(layers[layers.len() - 1].label, "outputs");
+        assert_eq!(layers[1].label, "hidden 1");
+
+        // Every edge leads from an earlier column to a later one
+        let column_of: Vec<_> = sizes
+            .iter()
+            .enumerate()
+            .flat_map(|(column, size)| (0..*size).map(move |_| column))
+            .collect();
+        for edge in genome.edges() {
+            assert!(column_of[edge.from] < column_of[edge.to]);
+        }
+
+        let svg = genome.to_svg();
+        assert_eq!(svg.matches("<circle").count(), genome.nodes.len());
+    }
+}
@@ -0,0 +1,80 @@
+use crate::*;
+
+/// Record of every structural mutation so far, so that the same mutation arising
+/// in different genomes receives the same innovation number and node id
+#[derive(Debug, Clone)]
+pub struct InnovationHistory {
+    next_node: usize,
+    next_innovation: usize,
+    connections: HashMap<(usize, usize), usize>, // Innovation number of each (from, to) pair
+    splits: HashMap<usize, usize>,               // Node id inserted into each split connection
+}
+
+impl InnovationHistory {
+    /// Starts a history agreeing with `Genome::minimal` genomes of the same shape
+    pub fn new(inputs: usize, outputs: usize) -> InnovationHistory {
+        let mut history = InnovationHistory {
+            next_node: inputs + outputs,
+            next_innovation: 0,
+            connections: HashMap::new(),
+            splits: HashMap::new(),
+        };
+
+        for from in 0..inputs {
+            for to in inputs..(inputs + outputs) {
+                history.connection(from, to);
+            }
+        }
+
+        history
+    }
+
+    /// Innovation number of a connection, allocating one if it is new
+    pub(crate) fn connection(&mut self, from: usize, to: usize) -> usize {
+        if let Some(innovation) = self.connections.get(&(from, to)) {
+            return *innovation;
+        }
+
+        let innovation = self.next_innovation;
+        self.next_innovation += 1;
+        self.connections.insert((from, to), innovation);
+        innovation
+    }
+
+    /// Id of the node splitting a connection, allocating one if it is new
+    pub(crate) fn split(&mut self, innovation: usize) -> usize {
+        if let Some(node) = self.splits.get(&innovation) {
+            return *node;
+        }
+
+        let node = self.next_node;
+        self.next_node += 1;
+        self.splits.insert(innovation, node);
+        node
+    }
+
+    pub(crate) fn existing_split(&self, innovation: usize) -> Option<usize> {
+        self.splits.get(&innovation).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reuses_innovations() {
+        let mut history = InnovationHistory::new(2, 1);
+
+        assert_eq!(history.connection(0, 2), 0);
+        assert_eq!(history.connection(1, 2), 1);
+        assert_eq!(history.connection(2, 0), 2);
+        assert_eq!(history.connection(2, 0), 2);
+
+        assert_eq!(history.existing_split(1), None);
+        assert_eq!(history.split(1), 3);
+        assert_eq!(history.split(1), 3);
+        assert_eq!(history.existing_split(1), Some(3));
+        assert_eq!(history.split(0), 4);
+    }
+}
@@ -0,0 +1,17 @@
+use std::collections::HashMap;
+use std::iter::once;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use lib_neural_network as nn;
+
+pub use self::{distance::*, gene::*, genome::*, innovation::*, mutation::*, network::*};
+
+mod distance;
+mod gene;
+mod genome;
+mod graph;
+mod innovation;
+mod mutation;
+mod network;
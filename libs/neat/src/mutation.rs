@@ -0,0 +1,143 @@
+use crate::*;
+
+/// Weight and structural mutations applied to a genome after crossover
+#[derive(Debug, Clone)]
+pub struct Mutation {
+    // Probability of changing each weight and bias
+    weight_rate: f32,
+    // Magnitude of weight and bias changes
+    weight_strength: f32,
+    // Probability of splitting a connection with a new node
+    add_node_rate: f32,
+    // Probability of connecting two unconnected nodes
+    add_connection_rate: f32,
+    // Probability of enabling or disabling a connection
+    toggle_rate: f32,
+    // Probability of a hidden node switching activation
+    activation_rate: f32,
+    // Activations hidden nodes can switch to
+    activations: Vec<nn::Activation>,
+}
+
+impl Mutation {
+    pub fn new(
+        weight_rate: f32,
+        weight_strength: f32,
+        add_node_rate: f32,
+        add_connection_rate: f32,
+        toggle_rate: f32,
+        activation_rate: f32,
+        activations: Vec<nn::Activation>,
+    ) -> Mutation {
+        assert!((0.0..=1.0).contains(&weight_rate));
+        assert!((0.0..=1.0).contains(&add_node_rate));
+        assert!((0.0..=1.0).contains(&add_connection_rate));
+        assert!((0.0..=1.0).contains(&toggle_rate));
+        assert!((0.0..=1.0).contains(&activation_rate));
+
+        Mutation {
+            weight_rate,
+            weight_strength,
+            add_node_rate,
+            add_connection_rate,
+            toggle_rate,
+            activation_rate,
+            activations,
+        }
+    }
+
+    pub fn mutate(
+        &self,
+        rng: &mut dyn rand::RngCore,
+        genome: &mut Genome,
+        history: &mut InnovationHistory,
+    ) {
+        genome.mutate_weights(rng, self.weight_rate, self.weight_strength);
+
+        if rng.gen_bool(self.add_node_rate as _) {
+            genome.add_node(rng, history);
+        }
+
+        if rng.gen_bool(self.add_connection_rate as _) {
+            genome.add_connection(rng, history);
+        }
+
+        if rng.gen_bool(self.toggle_rate as _) {
+            genome.toggle_connection(rng);
+        }
+
+        if rng.gen_bool(self.activation_rate as _) {
+            genome.change_activation(rng, &self.activations);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_no_mutation() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut history = InnovationHistory::new(3, 2);
+        let genome = Genome::minimal(&mut rng, 3, 2);
+        let mut child = genome.clone();
+
+        Mutation::new(0.0, 1.0, 0.0, 0.0, 0.0, 0.0, vec![]).mutate(
+            &mut rng,
+            &mut child,
+            &mut history,
+        );
+
+        assert_eq!(child.nodes, genome.nodes);
+        assert_eq!(child.connections, genome.connections);
+    }
+
+    #[test]
+    fn test_structural_mutation() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut history = InnovationHistory::new(3, 2);
+        let genome = Genome::minimal(&mut rng, 3, 2);
+        let mut child = genome.clone();
+
+        let mutation = Mutation::new(0.0, 1.0, 1.0, 1.0, 0.0, 0.0, vec![]);
+        for _ in 0..10 {
+            mutation.mutate(&mut rng, &mut child, &mut history);
+        }
+
+        assert_eq!(child.nodes.len(), genome.nodes.len() + 10);
+        assert!(child.connections.len() >= genome.connections.len() + 30);
+
+        // The phenotype is still a valid feed-forward network
+        let mut network = Network::from_genome(&child);
+        assert_eq!(network.propagate(vec![0.5, -0.5, 1.0]).len(), 2);
+    }
+
+    #[test]
+    fn test_activation_mutation() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut history = InnovationHistory::new(3, 2);
+        let mut genome = Genome::minimal(&mut rng, 3, 2);
+
+        Mutation::new(0.0, 1.0, 1.0, 0.0, 0.0, 0.0, vec![]).mutate(
+            &mut rng,
+            &mut genome,
+            &mut history,
+        );
+        Mutation::new(0.0, 1.0, 0.0, 0.0, 0.0, 1.0, vec![nn::Activation::Sine]).mutate(
+            &mut rng,
+            &mut genome,
+            &mut history,
+        );
+
+        // Only hidden nodes switch activation
+        for node in &genome.nodes {
+            match node.kind {
+                NodeKind::Hidden => assert_eq!(node.activation, nn::Activation::Sine),
+                _ => assert_eq!(node.activation, nn::Activation::Tanh),
+            }
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use crate::*;
+
+/// Feed-forward phenotype of a genome, built from its enabled connections.
+/// Input nodes pass their values through, every other node applies its own
+/// activation.
+#[derive(Debug, Clone)]
+pub struct Network {
+    inputs: usize,
+    outputs: usize,
+    nodes: Vec<Node>, // Non-input nodes, in evaluation order
+    values: Vec<f32>, // Latest value of each node, indexed by its position in the genome
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    idx: usize,
+    bias: f32,
+    activation: nn::Activation,
+    incoming: Vec<(usize, f32)>,
+}
+
+impl Network {
+    pub fn from_genome(genome: &Genome) -> Network {
+        let idx_of: HashMap<_, _> = genome
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| (node.id, idx))
+            .collect();
+
+        let mut incoming = vec![Vec::new(); genome.nodes.len()];
+        let mut outgoing = vec![Vec::new(); genome.nodes.len()];
+        for gene in genome.connections.iter().filter(|gene| gene.enabled) {
+            let (from, to) = (idx_of[&gene.from], idx_of[&gene.to]);
+            incoming[to].push((from, gene.weight));
+            outgoing[from].push(to);
+        }
+
+        // Kahn's algorithm, starting from the inputs
+        let mut pending: Vec<_> = incoming.iter().map(Vec::len).collect();
+        let mut ready: Vec<_> = (0..genome.nodes.len())
+            .filter(|idx| pending[*idx] == 0)
+            .collect();
+        let mut order = Vec::with_capacity(genome.nodes.len());
+
+        while let Some(idx) = ready.pop() {
+            order.push(idx);
+
+            for to in &outgoing[idx] {
+                pending[*to] -= 1;
+                if pending[*to] == 0 {
+                    ready.push(*to);
+                }
+            }
+        }
+
+        assert_eq!(order.len(), genome.nodes.len(), "Genome contains a cycle");
+
+        let nodes = order
+            .into_iter()
+            .filter(|idx| genome.nodes[*idx].kind != NodeKind::Input)
+            .map(|idx| Node {
+                idx,
+                bias: genome.nodes[idx].bias,
+                activation: genome.nodes[idx].activation,
+                incoming: std::mem::take(&mut incoming[idx]),
+            })
+            .collect();
+
+        Network {
+            inputs: genome.inputs,
+            outputs: genome.outputs,
+            nodes,
+            values: vec![0.0; genome.nodes.len()],
+        }
+    }
+
+    pub fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32> {
+        assert_eq!(inputs.len(), self.inputs);
+
+        self.values[..self.inputs].copy_from_slice(&inputs);
+
+        for node in &self.nodes {
+            let sum = node
+                .incoming
+                .iter()
+                .map(|(from, weight)| self.values[*from] * weight)
+                .sum::<f32>()
+                + node.bias;
+
+            self.values[node.idx] = node.activation.evaluate(sum);
+        }
+
+        self.values[self.inputs..self.inputs + self.outputs].to_vec()
+    }
+
+    /// Propagates like `propagate`, returning the inputs, then the values of
+    /// the hidden nodes and finally the outputs
+    pub fn trace(&mut self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        let outputs = self.propagate(inputs.clone());
+        let hidden = self.values[self.inputs + self.outputs..].to_vec();
+
+        vec![inputs, hidden, outputs]
+    }
+}
+
+impl nn::Propagate for Network {
+    fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32> {
+        Network::propagate(self, inputs)
+    }
+
+    fn trace(&mut self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        Network::trace(self, inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut history = InnovationHistory::new(2, 1);
+        let mut genome = Genome::minimal(&mut rng, 2, 1);
+        genome.nodes[2].bias = 0.1;
+        genome.connections[0].weight = 0.5;
+        genome.connections[1].weight = -1.0;
+
+        let mut network = Network::from_genome(&genome);
+        assert_relative_eq!(
+            network.propagate(vec![1.0, 0.25])[0],
+            (0.5 - 0.25 + 0.1f32).tanh()
+        );
+
+        // Splitting the only enabled connection routes it through a hidden node
+        genome.connections[0].enabled = false;
+        genome.add_node(&mut rng, &mut history);
+
+        let mut network = Network::from_genome(&genome);
+        assert_relative_eq!(
+            network.propagate(vec![1.0, 0.25])[0],
+            (0.1 - 0.25f32.tanh()).tanh()
+        );
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut history = InnovationHistory::new(2, 1);
+        let mut genome = Genome::minimal(&mut rng, 2, 1);
+        genome.add_node(&mut rng, &mut history);
+
+        let mut network = Network::from_genome(&genome);
+        let trace = network.trace(vec![1.0, 0.25]);
+        let lengths: Vec<_> = trace.iter().map(Vec::len).collect();
+
+        assert_eq!(lengths, vec![2, 1, 1]);
+        assert_eq!(trace[0], vec![1.0, 0.25]);
+        assert_eq!(trace[2], network.propagate(vec![1.0, 0.25]));
+    }
+
+    #[test]
+    fn test_node_activation() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut genome = Genome::minimal(&mut rng, 1, 1);
+        genome.connections[0].weight = 0.5;
+        genome.nodes[1].bias = 0.0;
+        genome.nodes[1].activation = nn::Activation::Gaussian;
+
+        let mut network = Network::from_genome(&genome);
+        assert_relative_eq!(network.propagate(vec![2.0])[0], (-1.0f32).exp());
+    }
+
+    #[test]
+    fn test_unconnected_output() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut genome = Genome::minimal(&mut rng, 1, 1);
+        genome.connections[0].enabled = false;
+        genome.nodes[1].bias = 0.5;
+
+        let mut network = Network::from_genome(&genome);
+        assert_relative_eq!(network.propagate(vec![1.0])[0], 0.5f32.tanh());
+    }
+}
@@ -0,0 +1,138 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use lib_neural_network::{
+    Activation, Initializer, LayerKind, LayerTopology, MatrixNetwork, Network, QuantizedNetwork,
+    Scratch, SparseNetwork,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+const INPUTS: usize = 16;
+
+fn topology(kind: LayerKind) -> Vec<LayerTopology> {
+    vec![
+        LayerTopology {
+            neurons: INPUTS,
+            activation: Activation::None,
+            kind: LayerKind::Dense,
+            initializer: Initializer::Uniform,
+        },
+        LayerTopology {
+            neurons: 16,
+            activation: Activation::ReLU,
+            kind,
+            initializer: Initializer::Uniform,
+        },
+        LayerTopology {
+            neurons: 4,
+            activation: Activation::Tanh,
+            kind: LayerKind::Dense,
+            initializer: Initializer::Uniform,
+        },
+    ]
+}
+
+/// Propagates a whole population of brains, the way `process_brains` does every tick
+fn propagate(c: &mut Criterion) {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+    for kind in [LayerKind::Dense, LayerKind::Gru] {
+        let mut group = c.benchmark_group(format!("propagate_{}", kind));
+
+        for population in [100, 1000] {
+            let mut networks: Vec<_> = (0..population)
+                .map(|_| Network::random(&mut rng, &topology(kind)))
+                .collect();
+            let mut matrices: Vec<_> = networks.iter().map(MatrixNetwork::from).collect();
+            let inputs: Vec<_> = (0..population * INPUTS)
+                .map(|n| (n % 7) as f32 / 7.0)
+                .collect();
+
+            group.bench_with_input(
+                BenchmarkId::new("per_neuron", population),
+                &inputs,
+                |b, inputs| {
+                    b.iter(|| {
+                        networks
+                            .iter_mut()
+                            .zip(inputs.chunks(INPUTS))
+                            .map(|(network, inputs)| network.propagate(inputs.to_vec()))
+                            .collect::<Vec<_>>()
+                    })
+                },
+            );
+
+            let mut outputs = Vec::new();
+            let mut scratch = Scratch::default();
+            group.bench_with_input(
+                BenchmarkId::new("batch", population),
+                &inputs,
+                |b, inputs| {
+                    b.iter(|| {
+                        MatrixNetwork::propagate_batch(
+                            &mut matrices,
+                            inputs,
+                            &mut outputs,
+                            &mut scratch,
+                        )
+                    })
+                },
+            );
+        }
+
+        group.finish();
+    }
+}
+
+/// Propagates pruned and quantized copies of the same dense networks
+fn compressed(c: &mut Criterion) {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let mut group = c.benchmark_group("propagate_compressed");
+
+    let population = 1000;
+    let mut networks: Vec<_> = (0..population)
+        .map(|_| Network::random(&mut rng, &topology(LayerKind::Dense)))
+        .collect();
+    let inputs: Vec<_> = (0..INPUTS).map(|n| (n % 7) as f32 / 7.0).collect();
+
+    let mut quantized: Vec<_> = networks
+        .iter()
+        .map(|network| QuantizedNetwork::calibrate(network, std::slice::from_ref(&inputs)))
+        .collect();
+    let mut sparse: Vec<_> = networks
+        .iter_mut()
+        .map(|network| {
+            network.prune(0.5);
+            SparseNetwork::from(&*network)
+        })
+        .collect();
+
+    group.bench_function("float", |b| {
+        b.iter(|| {
+            networks
+                .iter_mut()
+                .map(|network| network.propagate(inputs.clone()))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("sparse", |b| {
+        b.iter(|| {
+            sparse
+                .iter_mut()
+                .map(|network| network.propagate(inputs.clone()))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("quantized", |b| {
+        b.iter(|| {
+            quantized
+                .iter_mut()
+                .map(|network| network.propagate(inputs.clone()))
+                .collect::<Vec<_>>()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, propagate, compressed);
+criterion_main!(benches);
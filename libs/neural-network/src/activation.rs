@@ -1,8 +1,30 @@
-use std::f32::consts::E;
+use std::f32::consts::PI;
+use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy)]
+use crate::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Activation {
     ReLU,
+    /// ReLU letting through negative inputs scaled by a fixed slope
+    LeakyReLU(f32),
+    /// Leaky ReLU whose slope is evolved along with the weights of NEAT
+    /// genomes; layered networks keep it at its initial slope
+    PReLU(f32),
+    /// Exponential linear unit, with α = 1
+    Elu,
+    /// Gaussian error linear unit, in its tanh approximation
+    Gelu,
+    Softplus,
+    /// x·sigmoid(x)
+    Swish,
+    /// exp(-x²), as used by CPPNs for symmetric patterns
+    Gaussian,
+    Sine,
+    Step,
+    /// Tanh approximated by clamping to -1..=1
+    HardTanh,
     Sigmoid,
     Softmax,
     Tanh,
@@ -10,37 +32,419 @@ pub enum Activation {
 }
 
 impl Activation {
-    pub(crate) fn apply(&self, inputs: Vec<f32>) -> Vec<f32> {
-        let f = match *self {
-            Activation::ReLU => relu,
-            Activation::Sigmoid => sigmoid,
-            Activation::Softmax => softmax,
-            Activation::Tanh => tanh,
-            Activation::None => none,
+    pub(crate) fn apply(&self, mut inputs: Vec<f32>) -> Vec<f32> {
+        self.apply_in_place(&mut inputs);
+        inputs
+    }
+
+    pub(crate) fn apply_in_place(&self, inputs: &mut [f32]) {
+        match *self {
+            Activation::ReLU => simd::relu(inputs),
+            Activation::Sigmoid => simd::sigmoid(inputs),
+            Activation::Softmax => softmax(inputs),
+            Activation::Tanh => simd::tanh(inputs),
+            Activation::None => {}
+            _ => inputs.iter_mut().for_each(|x| *x = self.evaluate(*x)),
+        }
+    }
+
+    /// Applies the activation to a single value. Softmax normalises across
+    /// a whole layer, so a lone value always maps to 1.
+    pub fn evaluate(&self, x: f32) -> f32 {
+        match *self {
+            Activation::ReLU => x.max(0.0),
+            Activation::LeakyReLU(slope) | Activation::PReLU(slope) => {
+                if x > 0.0 {
+                    x
+                } else {
+                    slope * x
+                }
+            }
+            Activation::Elu => {
+                if x > 0.0 {
+                    x
+                } else {
+                    x.exp_m1()
+                }
+            }
+            Activation::Gelu => {
+                0.5 * x * (1.0 + ((2.0 / PI).sqrt() * (x + 0.044715 * x.powi(3))).tanh())
+            }
+            // Rearranged so that large inputs don't overflow
+            Activation::Softplus => x.max(0.0) + (-x.abs()).exp().ln_1p(),
+            Activation::Swish => x * sigmoid(x),
+            Activation::Gaussian => (-x * x).exp(),
+            Activation::Sine => x.sin(),
+            Activation::Step => step(x),
+            Activation::HardTanh => x.clamp(-1.0, 1.0),
+            Activation::Sigmoid => sigmoid(x),
+            Activation::Softmax => 1.0,
+            Activation::Tanh => x.tanh(),
+            Activation::None => x,
+        }
+    }
+
+    /// Derivative of the activation at the pre-activation `x`. Step has a
+    /// zero gradient almost everywhere, and so does Softmax taken one value at
+    /// a time; layers backpropagate through the whole Softmax instead.
+    pub fn derivative(&self, x: f32) -> f32 {
+        match *self {
+            Activation::ReLU => step(x),
+            Activation::LeakyReLU(slope) | Activation::PReLU(slope) => {
+                if x > 0.0 {
+                    1.0
+                } else {
+                    slope
+                }
+            }
+            Activation::Elu => {
+                if x > 0.0 {
+                    1.0
+                } else {
+                    x.exp()
+                }
+            }
+            Activation::Gelu => {
+                let scale = (2.0 / PI).sqrt();
+                let t = (scale * (x + 0.044715 * x.powi(3))).tanh();
+
+                0.5 * (1.0 + t) + 0.5 * x * (1.0 - t * t) * scale * (1.0 + 3.0 * 0.044715 * x * x)
+            }
+            Activation::Softplus => sigmoid(x),
+            Activation::Swish => {
+                let s = sigmoid(x);
+                s + x * s * (1.0 - s)
+            }
+            Activation::Gaussian => -2.0 * x * (-x * x).exp(),
+            Activation::Sine => x.cos(),
+            Activation::Step | Activation::Softmax => 0.0,
+            Activation::HardTanh => {
+                if x.abs() < 1.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Activation::Sigmoid => {
+                let s = sigmoid(x);
+                s * (1.0 - s)
+            }
+            Activation::Tanh => 1.0 - x.tanh().powi(2),
+            Activation::None => 1.0,
+        }
+    }
+
+    /// Turns the gradient of the loss with respect to a layer's `outputs`
+    /// into its gradient with respect to the layer's `preactivations`
+    pub(crate) fn backpropagate(
+        &self,
+        preactivations: &[f32],
+        outputs: &[f32],
+        gradients: &[f32],
+    ) -> Vec<f32> {
+        match *self {
+            // Every output of a softmax depends on every input
+            Activation::Softmax => {
+                let weighted: f32 = outputs.iter().zip(gradients).map(|(y, g)| y * g).sum();
+
+                outputs
+                    .iter()
+                    .zip(gradients)
+                    .map(|(y, g)| y * (g - weighted))
+                    .collect()
+            }
+            _ => preactivations
+                .iter()
+                .zip(gradients)
+                .map(|(x, g)| self.derivative(*x) * g)
+                .collect(),
+        }
+    }
+
+    /// Parameter evolved along with a NEAT genome's weights, which only PReLU
+    /// has; layered networks leave it fixed
+    pub fn parameter_mut(&mut self) -> Option<&mut f32> {
+        match self {
+            Activation::PReLU(slope) => Some(slope),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Activation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match *self {
+            Activation::ReLU => "relu",
+            Activation::LeakyReLU(slope) => return write!(f, "leakyrelu({})", slope),
+            Activation::PReLU(slope) => return write!(f, "prelu({})", slope),
+            Activation::Elu => "elu",
+            Activation::Gelu => "gelu",
+            Activation::Softplus => "softplus",
+            Activation::Swish => "swish",
+            Activation::Gaussian => "gaussian",
+            Activation::Sine => "sine",
+            Activation::Step => "step",
+            Activation::HardTanh => "hardtanh",
+            Activation::Sigmoid => "sigmoid",
+            Activation::Softmax => "softmax",
+            Activation::Tanh => "tanh",
+            Activation::None => "none",
         };
 
-        f(inputs)
+        f.write_str(name)
     }
 }
 
-fn relu(inputs: Vec<f32>) -> Vec<f32> {
-    inputs.iter().map(|x| x.max(0.0)).collect()
+/// Parses an activation's name, followed by its parameter in parentheses for
+/// parametric activations, e.g. `leakyrelu(0.1)`. The parameter defaults to
+/// 0.01 for LeakyReLU and to 0.25 for PReLU.
+impl FromStr for Activation {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Activation, String> {
+        let lowercase = name.trim().to_ascii_lowercase();
+        let (base, parameter) = match lowercase.split_once('(') {
+            Some((base, rest)) => {
+                let parameter = rest
+                    .strip_suffix(')')
+                    .and_then(|parameter| parameter.trim().parse::<f32>().ok())
+                    .ok_or_else(|| format!("Invalid activation parameter: {}", name))?;
+
+                (base.trim(), Some(parameter))
+            }
+            None => (lowercase.as_str(), None),
+        };
+
+        let activation = match base {
+            "relu" => Activation::ReLU,
+            "leakyrelu" => Activation::LeakyReLU(parameter.unwrap_or(0.01)),
+            "prelu" => Activation::PReLU(parameter.unwrap_or(0.25)),
+            "elu" => Activation::Elu,
+            "gelu" => Activation::Gelu,
+            "softplus" => Activation::Softplus,
+            "swish" => Activation::Swish,
+            "gaussian" => Activation::Gaussian,
+            "sine" => Activation::Sine,
+            "step" => Activation::Step,
+            "hardtanh" => Activation::HardTanh,
+            "sigmoid" => Activation::Sigmoid,
+            "softmax" => Activation::Softmax,
+            "tanh" => Activation::Tanh,
+            "none" => Activation::None,
+            _ => return Err(format!("Unknown activation: {}", name)),
+        };
+
+        match (activation, parameter) {
+            (Activation::LeakyReLU(_) | Activation::PReLU(_), _) | (_, None) => Ok(activation),
+            _ => Err(format!("Activation takes no parameter: {}", name)),
+        }
+    }
 }
 
-fn sigmoid(inputs: Vec<f32>) -> Vec<f32> {
-    inputs.iter().map(|x| 1.0 / (1.0 + E.powf(-x))).collect()
+fn step(x: f32) -> f32 {
+    if x > 0.0 {
+        1.0
+    } else {
+        0.0
+    }
 }
 
-fn softmax(inputs: Vec<f32>) -> Vec<f32> {
-    let exponential_inputs: Vec<f32> = inputs.iter().map(|x| x.exp()).collect();
-    let sum: f32 = exponential_inputs.iter().sum();
-    exponential_inputs.iter().map(|x| x / sum).collect()
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
 }
 
-fn tanh(inputs: Vec<f32>) -> Vec<f32> {
-    inputs.iter().map(|x| x.tanh()).collect()
+/// The largest input is subtracted first, so that large inputs can't
+/// overflow to infinity and turn the outputs into NaN
+fn softmax(inputs: &mut [f32]) {
+    let max = inputs.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    inputs.iter_mut().for_each(|x| *x = (*x - max).exp());
+    let sum: f32 = inputs.iter().sum();
+    inputs.iter_mut().for_each(|x| *x /= sum);
 }
 
-fn none(inputs: Vec<f32>) -> Vec<f32> {
-    inputs
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    mod evaluate {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let cases = [
+                (Activation::LeakyReLU(0.1), -2.0, -0.2),
+                (Activation::PReLU(0.5), -2.0, -1.0),
+                (Activation::PReLU(0.5), 2.0, 2.0),
+                (Activation::Elu, -1.0, -0.63212055),
+                (Activation::Gelu, 1.0, 0.84119195),
+                (Activation::Softplus, 0.0, 2.0f32.ln()),
+                (Activation::Softplus, 100.0, 100.0),
+                (Activation::Swish, 1.0, 0.7310586),
+                (Activation::Gaussian, 1.0, (-1.0f32).exp()),
+                (Activation::Sine, PI / 2.0, 1.0),
+                (Activation::Step, 0.5, 1.0),
+                (Activation::Step, 0.0, 0.0),
+                (Activation::HardTanh, -3.0, -1.0),
+                (Activation::HardTanh, 0.5, 0.5),
+            ];
+
+            for (activation, x, expected) in cases {
+                assert_relative_eq!(activation.evaluate(x), expected, epsilon = 1e-6);
+            }
+        }
+
+        #[test]
+        fn test_matches_apply() {
+            let inputs = vec![-2.0, -0.5, 0.0, 0.5, 2.0];
+
+            for activation in [
+                Activation::ReLU,
+                Activation::Sigmoid,
+                Activation::Tanh,
+                Activation::Gelu,
+                Activation::Swish,
+            ] {
+                let expected: Vec<_> = inputs.iter().map(|x| activation.evaluate(*x)).collect();
+                let actual = activation.apply(inputs.clone());
+
+                assert_relative_eq!(actual.as_slice(), expected.as_slice(), epsilon = 1e-6);
+            }
+        }
+    }
+
+    mod derivative {
+        use super::*;
+
+        #[test]
+        fn test_matches_finite_differences() {
+            let h = 1e-3;
+
+            for activation in [
+                Activation::ReLU,
+                Activation::LeakyReLU(0.1),
+                Activation::PReLU(0.3),
+                Activation::Elu,
+                Activation::Gelu,
+                Activation::Softplus,
+                Activation::Swish,
+                Activation::Gaussian,
+                Activation::Sine,
+                Activation::Step,
+                Activation::HardTanh,
+                Activation::Sigmoid,
+                Activation::Softmax,
+                Activation::Tanh,
+                Activation::None,
+            ] {
+                // Away from the kinks at 0, ±1
+                for x in [-2.3, -0.7, 0.4, 1.6] {
+                    let expected =
+                        (activation.evaluate(x + h) - activation.evaluate(x - h)) / (2.0 * h);
+
+                    assert_relative_eq!(activation.derivative(x), expected, epsilon = 1e-2);
+                }
+            }
+        }
+
+        #[test]
+        fn test_softmax_backpropagate() {
+            let preactivations = [0.5, -1.0, 2.0];
+            let outputs = Activation::Softmax.apply(preactivations.to_vec());
+            let gradients = [1.0, 0.0, -1.0];
+            let h = 1e-3;
+
+            let actual = Activation::Softmax.backpropagate(&preactivations, &outputs, &gradients);
+
+            for (n, actual) in actual.iter().enumerate() {
+                let loss = |offset: f32| {
+                    let mut inputs = preactivations.to_vec();
+                    inputs[n] += offset;
+
+                    let outputs = Activation::Softmax.apply(inputs);
+                    outputs
+                        .iter()
+                        .zip(&gradients)
+                        .map(|(y, g)| y * g)
+                        .sum::<f32>()
+                };
+                let expected = (loss(h) - loss(-h)) / (2.0 * h);
+
+                assert_relative_eq!(*actual, expected, epsilon = 1e-3);
+            }
+        }
+    }
+
+    mod softmax {
+        use super::*;
+
+        #[test]
+        fn test_large_inputs() {
+            let outputs = Activation::Softmax.apply(vec![1000.0, 1000.0, -1000.0]);
+
+            assert_relative_eq!(outputs.as_slice(), [0.5, 0.5, 0.0].as_ref());
+        }
+    }
+
+    mod parameter_mut {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut prelu = Activation::PReLU(0.25);
+            *prelu.parameter_mut().unwrap() = 0.5;
+
+            assert_eq!(prelu, Activation::PReLU(0.5));
+            assert_eq!(Activation::LeakyReLU(0.25).parameter_mut(), None);
+        }
+    }
+
+    mod from_str {
+        use super::*;
+
+        #[test]
+        fn test_round_trip() {
+            for activation in [
+                Activation::ReLU,
+                Activation::LeakyReLU(0.1),
+                Activation::PReLU(-0.5),
+                Activation::Elu,
+                Activation::Gelu,
+                Activation::Softplus,
+                Activation::Swish,
+                Activation::Gaussian,
+                Activation::Sine,
+                Activation::Step,
+                Activation::HardTanh,
+                Activation::Sigmoid,
+                Activation::Softmax,
+                Activation::Tanh,
+                Activation::None,
+            ] {
+                assert_eq!(activation.to_string().parse(), Ok(activation));
+            }
+        }
+
+        #[test]
+        fn test_case_insensitive() {
+            assert_eq!("ReLU".parse(), Ok(Activation::ReLU));
+        }
+
+        #[test]
+        fn test_default_parameters() {
+            assert_eq!("leakyrelu".parse(), Ok(Activation::LeakyReLU(0.01)));
+            assert_eq!("PReLU( 0.3 )".parse(), Ok(Activation::PReLU(0.3)));
+        }
+
+        #[test]
+        fn test_unknown() {
+            assert!("mish".parse::<Activation>().is_err());
+        }
+
+        #[test]
+        fn test_invalid_parameter() {
+            assert!("leakyrelu(slope)".parse::<Activation>().is_err());
+            assert!("tanh(0.5)".parse::<Activation>().is_err());
+        }
+    }
 }
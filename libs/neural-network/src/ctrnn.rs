@@ -0,0 +1,261 @@
+use crate::*;
+
+/// Continuous-time recurrent neural network of fully connected neurons,
+/// integrated with the Euler method:
+///
+/// τᵢ·dyᵢ/dt = -yᵢ + Σⱼ wᵢⱼ·tanh(yⱼ + θⱼ) + Σₖ uᵢₖ·xₖ
+///
+/// The first `outputs` neurons are read as outputs, through tanh(yᵢ + θᵢ).
+#[derive(Debug, Clone)]
+pub struct Ctrnn {
+    inputs: usize,
+    outputs: usize,
+    dt: f32,
+    neurons: Vec<CtrnnNeuron>,
+    state: Vec<f32>,
+}
+
+#[derive(Debug, Clone)]
+struct CtrnnNeuron {
+    log_tau: f32, // Evolved in log space, so that mutations scale time constants evenly
+    bias: f32,
+    input_weights: Vec<f32>,
+    weights: Vec<f32>,
+}
+
+impl Ctrnn {
+    pub fn random(
+        rng: &mut dyn rand::RngCore,
+        inputs: usize,
+        neurons: usize,
+        outputs: usize,
+        dt: f32,
+    ) -> Ctrnn {
+        let neurons = (0..neurons)
+            .map(|_| CtrnnNeuron {
+                log_tau: rng.gen_range(0.0..=5.0f32.ln()),
+                bias: rng.gen_range(-1.0..=1.0),
+                input_weights: (0..inputs).map(|_| rng.gen_range(-1.0..=1.0)).collect(),
+                weights: (0..neurons).map(|_| rng.gen_range(-1.0..=1.0)).collect(),
+            })
+            .collect();
+
+        Ctrnn::new(inputs, outputs, dt, neurons)
+    }
+
+    /// Builds a network from parameters in the order returned by `weights`
+    pub fn from_weights(
+        inputs: usize,
+        neurons: usize,
+        outputs: usize,
+        dt: f32,
+        weights: impl IntoIterator<Item = f32>,
+    ) -> Ctrnn {
+        let mut weights = weights.into_iter();
+        let mut next = || weights.next().expect("Not enough weights!");
+
+        let neurons = (0..neurons)
+            .map(|_| CtrnnNeuron {
+                log_tau: next(),
+                bias: next(),
+                input_weights: (0..inputs).map(|_| next()).collect(),
+                weights: (0..neurons).map(|_| next()).collect(),
+            })
+            .collect();
+
+        if weights.next().is_some() {
+            panic!("Too many weights!");
+        }
+
+        Ctrnn::new(inputs, outputs, dt, neurons)
+    }
+
+    /// Number of parameters of a network with the given shape
+    pub fn parameters(inputs: usize, neurons: usize) -> usize {
+        neurons * (2 + inputs + neurons)
+    }
+
+    /// Log time constant, bias, input weights and recurrent weights of each neuron
+    pub fn weights(&self) -> impl Iterator<Item = f32> + '_ {
+        use std::iter::once;
+
+        self.neurons.iter().flat_map(|neuron| {
+            once(neuron.log_tau)
+                .chain(once(neuron.bias))
+                .chain(neuron.input_weights.iter().copied())
+                .chain(neuron.weights.iter().copied())
+        })
+    }
+
+    /// Advances the network by a single timestep
+    pub fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32> {
+        assert_eq!(inputs.len(), self.inputs);
+
+        let firing: Vec<_> = self
+            .state
+            .iter()
+            .zip(&self.neurons)
+            .map(|(y, neuron)| (y + neuron.bias).tanh())
+            .collect();
+
+        for (y, neuron) in self.state.iter_mut().zip(&self.neurons) {
+            let recurrent = simd::dot(&neuron.weights, &firing);
+            let external = simd::dot(&neuron.input_weights, &inputs);
+
+            *y += self.dt / neuron.tau(self.dt) * (-*y + recurrent + external);
+        }
+
+        self.state
+            .iter()
+            .zip(&self.neurons)
+            .take(self.outputs)
+            .map(|(y, neuron)| (y + neuron.bias).tanh())
+            .collect()
+    }
+
+    /// Advances like `propagate`, returning the inputs, then the firing rates
+    /// of the neurons not read as outputs and finally the outputs
+    pub fn trace(&mut self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        let outputs = self.propagate(inputs.clone());
+        let hidden = self
+            .state
+            .iter()
+            .zip(&self.neurons)
+            .skip(self.outputs)
+            .map(|(y, neuron)| (y + neuron.bias).tanh())
+            .collect();
+
+        vec![inputs, hidden, outputs]
+    }
+
+    /// Returns every neuron to rest
+    pub fn reset(&mut self) {
+        self.state.iter_mut().for_each(|y| *y = 0.0);
+    }
+
+    fn new(inputs: usize, outputs: usize, dt: f32, neurons: Vec<CtrnnNeuron>) -> Ctrnn {
+        assert!(dt > 0.0);
+        assert!(outputs <= neurons.len());
+
+        Ctrnn {
+            inputs,
+            outputs,
+            dt,
+            state: vec![0.0; neurons.len()],
+            neurons,
+        }
+    }
+}
+
+impl CtrnnNeuron {
+    /// Time constants shorter than a timestep would make the integration unstable
+    fn tau(&self, dt: f32) -> f32 {
+        self.log_tau.exp().max(dt)
+    }
+}
+
+impl Propagate for Ctrnn {
+    fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32> {
+        Ctrnn::propagate(self, inputs)
+    }
+
+    fn trace(&mut self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        Ctrnn::trace(self, inputs)
+    }
+
+    fn reset(&mut self) {
+        Ctrnn::reset(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    mod from_weights {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let weights: Vec<_> = (0..Ctrnn::parameters(3, 4))
+                .map(|n| n as f32 / 10.0)
+                .collect();
+            let network = Ctrnn::from_weights(3, 4, 2, 0.1, weights.clone());
+            let actual: Vec<_> = network.weights().collect();
+
+            assert_eq!(weights.len(), 4 * (2 + 3 + 4));
+            assert_relative_eq!(actual.as_slice(), weights.as_slice());
+        }
+
+        #[test]
+        #[should_panic(expected = "Too many weights!")]
+        fn test_too_many_weights() {
+            Ctrnn::from_weights(1, 1, 1, 0.1, vec![0.0; 5]);
+        }
+    }
+
+    mod propagate {
+        use super::*;
+
+        #[test]
+        fn test_leaky_integration() {
+            // τ = 2, no bias or recurrence, unit input weight
+            let mut network = Ctrnn::from_weights(1, 1, 1, 0.5, vec![2.0f32.ln(), 0.0, 1.0, 0.0]);
+
+            // y moves a quarter of the way towards the input each step
+            assert_relative_eq!(network.propagate(vec![1.0])[0], 0.25f32.tanh());
+            assert_relative_eq!(network.propagate(vec![1.0])[0], 0.4375f32.tanh());
+
+            // Then decays back towards rest without input
+            assert_relative_eq!(network.propagate(vec![0.0])[0], 0.328125f32.tanh());
+
+            network.reset();
+            assert_relative_eq!(network.propagate(vec![1.0])[0], 0.25f32.tanh());
+        }
+
+        #[test]
+        fn test_short_time_constants_are_clamped() {
+            let mut network = Ctrnn::from_weights(1, 1, 1, 0.5, vec![-10.0, 0.0, 1.0, 0.0]);
+
+            // τ = dt, so y jumps straight to the input
+            assert_relative_eq!(network.propagate(vec![1.0])[0], 1.0f32.tanh());
+            assert_relative_eq!(network.propagate(vec![1.0])[0], 1.0f32.tanh());
+        }
+
+        #[test]
+        fn test_oscillates_without_input() {
+            // Two self-exciting neurons, one exciting and the other inhibiting its partner
+            let mut network = Ctrnn::from_weights(
+                1,
+                2,
+                2,
+                0.1,
+                vec![
+                    0.0, 0.1, 0.0, 1.5, 4.0, // First neuron
+                    0.0, 0.0, 0.0, -4.0, 1.5, // Second neuron
+                ],
+            );
+
+            let outputs: Vec<_> = (0..200).map(|_| network.propagate(vec![0.0])[0]).collect();
+            let sign_changes = outputs
+                .windows(2)
+                .filter(|pair| pair[0].signum() != pair[1].signum())
+                .count();
+
+            assert!(sign_changes >= 2);
+        }
+
+        #[test]
+        fn test_random() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut network = Ctrnn::random(&mut rng, 3, 5, 2, 0.1);
+
+            let outputs = network.propagate(vec![0.5, -0.5, 1.0]);
+            assert_eq!(outputs.len(), 2);
+            assert!(outputs.iter().all(|output| output.abs() <= 1.0));
+        }
+    }
+}
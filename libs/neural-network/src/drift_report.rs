@@ -0,0 +1,115 @@
+use crate::*;
+
+/// How far the outputs of an approximated network, e.g. a pruned or
+/// quantized one, drift from those of the original on recorded inputs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DriftReport {
+    pub mean_error: f32, // Mean absolute difference over every output
+    pub max_error: f32,  // Largest absolute difference of any output
+    pub agreement: f32,  // Fraction of inputs whose largest output is the same in both
+}
+
+impl DriftReport {
+    /// Propagates the `inputs` in order through both networks, starting
+    /// from a reset state so that recurrent layers see the same history
+    pub fn measure(
+        reference: &mut dyn Propagate,
+        candidate: &mut dyn Propagate,
+        inputs: &[Vec<f32>],
+    ) -> DriftReport {
+        reference.reset();
+        candidate.reset();
+
+        let mut total_error = 0.0;
+        let mut outputs = 0;
+        let mut max_error = 0.0f32;
+        let mut agreements = 0;
+
+        for inputs in inputs {
+            let expected = reference.propagate(inputs.clone());
+            let actual = candidate.propagate(inputs.clone());
+            assert_eq!(actual.len(), expected.len());
+
+            for (actual, expected) in actual.iter().zip(&expected) {
+                let error = (actual - expected).abs();
+
+                total_error += error;
+                max_error = max_error.max(error);
+            }
+
+            outputs += expected.len();
+            agreements += (argmax(&actual) == argmax(&expected)) as usize;
+        }
+
+        DriftReport {
+            mean_error: total_error / outputs.max(1) as f32,
+            max_error,
+            agreement: agreements as f32 / inputs.len().max(1) as f32,
+        }
+    }
+}
+
+fn argmax(values: &[f32]) -> Option<usize> {
+    values
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(idx, _)| idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn layers() -> Vec<LayerTopology> {
+        vec![
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::None,
+                kind: LayerKind::Dense,
+                initializer: Initializer::Uniform,
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::None,
+                kind: LayerKind::Dense,
+                initializer: Initializer::Uniform,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_identical() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network = Network::random(&mut rng, &layers());
+        let mut copy = network.clone();
+
+        let report = DriftReport::measure(&mut network, &mut copy, &[vec![0.5], vec![-1.0]]);
+        assert_eq!(
+            report,
+            DriftReport {
+                mean_error: 0.0,
+                max_error: 0.0,
+                agreement: 1.0
+            }
+        );
+    }
+
+    #[test]
+    fn test() {
+        // Outputs x and 0.5 against x and 0.5 + 0.1x
+        let mut reference = Network::from_weights(&layers(), vec![0.0, 1.0, 0.5, 0.0]);
+        let mut candidate = Network::from_weights(&layers(), vec![0.0, 1.0, 0.5, 0.1]);
+
+        let report = DriftReport::measure(&mut reference, &mut candidate, &[vec![1.0], vec![0.0]]);
+        assert_relative_eq!(report.mean_error, 0.025);
+        assert_relative_eq!(report.max_error, 0.1);
+        assert_relative_eq!(report.agreement, 1.0);
+
+        let report = DriftReport::measure(&mut reference, &mut candidate, &[vec![0.55]]);
+        assert_relative_eq!(report.agreement, 0.0);
+    }
+}
@@ -0,0 +1,421 @@
+use std::fmt::Write;
+
+use crate::*;
+
+/// Colour of edges carrying positive and negative weights
+const POSITIVE: &str = "#2166ac";
+const NEGATIVE: &str = "#b2182b";
+
+/// Thickest edge, drawn for the largest weight in the network
+const MAX_EDGE_WIDTH: f32 = 4.0;
+const MIN_EDGE_WIDTH: f32 = 0.5;
+
+const NODE_RADIUS: f32 = 12.0;
+const COLUMN_SPACING: f32 = 160.0;
+const ROW_SPACING: f32 = 40.0;
+const MARGIN: f32 = 60.0;
+
+/// A network drawn as columns of nodes joined by weighted edges, which can
+/// be rendered to Graphviz DOT or to a standalone SVG
+pub trait Graph {
+    /// Columns of nodes, from the inputs to the outputs. Nodes are numbered
+    /// across columns in this order.
+    fn layers(&self) -> Vec<GraphLayer>;
+
+    fn edges(&self) -> Vec<GraphEdge>;
+
+    fn to_dot(&self) -> String {
+        dot(&self.layers(), &self.edges())
+    }
+
+    fn to_svg(&self) -> String {
+        svg(&self.layers(), &self.edges())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphLayer {
+    pub label: String,      // e.g. the kind and activation of the layer
+    pub nodes: Vec<String>, // Label of each node, e.g. its bias
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GraphEdge {
+    pub from: usize,
+    pub to: usize,
+    pub weight: f32,
+}
+
+impl Graph for Network {
+    /// Recurrent connections join a layer to itself; GRU layers are drawn
+    /// through their candidate neurons, leaving out the gates
+    fn layers(&self) -> Vec<GraphLayer> {
+        let topology = self.topology();
+        let inputs = GraphLayer {
+            label: "inputs".to_string(),
+            nodes: (0..topology[0].neurons).map(|n| n.to_string()).collect(),
+        };
+
+        std::iter::once(inputs)
+            .chain(self.layers.iter().enumerate().map(|(n, layer)| {
+                GraphLayer {
+                    label: format!("{}: {} {}", n + 1, layer.kind, layer.activation),
+                    nodes: layer
+                        .outputs()
+                        .iter()
+                        .map(|neuron| format!("{:+.2}", neuron.bias))
+                        .collect(),
+                }
+            }))
+            .collect()
+    }
+
+    fn edges(&self) -> Vec<GraphEdge> {
+        let mut edges = Vec::new();
+        let mut offset = 0;
+
+        for layer in &self.layers {
+            let neurons = layer.outputs();
+            let inputs = neurons[0].weights.len() - layer.state.len();
+            let first = offset + inputs;
+
+            for (to, neuron) in neurons.iter().enumerate() {
+                for (from, weight) in neuron.weights.iter().enumerate() {
+                    // Weights past the inputs feed back the layer's own outputs
+                    let from = if from < inputs {
+                        offset + from
+                    } else {
+                        first + from - inputs
+                    };
+
+                    edges.push(GraphEdge {
+                        from,
+                        to: first + to,
+                        weight: *weight,
+                    });
+                }
+            }
+
+            offset = first;
+        }
+
+        edges
+    }
+}
+
+fn dot(layers: &[GraphLayer], edges: &[GraphEdge]) -> String {
+    let mut dot = String::new();
+    let max_weight = max_weight(edges);
+
+    writeln!(dot, "digraph network {{").unwrap();
+    writeln!(dot, "    rankdir=LR;").unwrap();
+    writeln!(dot, "    node [shape=circle];").unwrap();
+
+    let mut id = 0;
+    for (n, layer) in layers.iter().enumerate() {
+        writeln!(dot, "    subgraph cluster_{} {{", n).unwrap();
+        writeln!(dot, "        label=\"{}\";", escape(&layer.label)).unwrap();
+
+        for label in &layer.nodes {
+            writeln!(dot, "        n{} [label=\"{}\"];", id, escape(label)).unwrap();
+            id += 1;
+        }
+
+        writeln!(dot, "    }}").unwrap();
+    }
+
+    for edge in edges {
+        writeln!(
+            dot,
+            "    n{} -> n{} [color=\"{}\", penwidth={:.2}, tooltip=\"{:.4}\"];",
+            edge.from,
+            edge.to,
+            color(edge.weight),
+            edge_width(edge.weight, max_weight),
+            edge.weight
+        )
+        .unwrap();
+    }
+
+    writeln!(dot, "}}").unwrap();
+    dot
+}
+
+fn svg(layers: &[GraphLayer], edges: &[GraphEdge]) -> String {
+    let rows = layers
+        .iter()
+        .map(|layer| layer.nodes.len())
+        .max()
+        .unwrap_or(0);
+    let width = 2.0 * MARGIN + COLUMN_SPACING * layers.len().saturating_sub(1) as f32;
+    let height = 2.0 * MARGIN + ROW_SPACING * rows.saturating_sub(1) as f32;
+
+    // Every column is centred vertically
+    let positions: Vec<_> = layers
+        .iter()
+        .enumerate()
+        .flat_map(|(column, layer)| {
+            let top = (height - ROW_SPACING * (layer.nodes.len() as f32 - 1.0)) / 2.0;
+
+            (0..layer.nodes.len()).map(move |row| {
+                (
+                    MARGIN + COLUMN_SPACING * column as f32,
+                    top + ROW_SPACING * row as f32,
+                )
+            })
+        })
+        .collect();
+
+    let mut svg = String::new();
+    let max_weight = max_weight(edges);
+
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\" font-size=\"10\">",
+        w = width,
+        h = height
+    )
+    .unwrap();
+
+    for edge in edges {
+        let (x1, y1) = positions[edge.from];
+        let (x2, y2) = positions[edge.to];
+        let stroke = format!(
+            "stroke=\"{}\" stroke-width=\"{:.2}\" fill=\"none\"",
+            color(edge.weight),
+            edge_width(edge.weight, max_weight)
+        );
+
+        if (x1 - x2).abs() < f32::EPSILON {
+            // Recurrent connections loop out to the right of their layer
+            let bulge = x1 + NODE_RADIUS + ROW_SPACING;
+            let spread = ROW_SPACING / 2.0;
+            writeln!(
+                svg,
+                "  <path d=\"M {} {} C {} {} {} {} {} {}\" {}><title>{:.4}</title></path>",
+                x1,
+                y1,
+                bulge,
+                y1 - spread,
+                bulge,
+                y2 + spread,
+                x2,
+                y2,
+                stroke,
+                edge.weight
+            )
+            .unwrap();
+        } else {
+            writeln!(
+                svg,
+                "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {}><title>{:.4}</title></line>",
+                x1, y1, x2, y2, stroke, edge.weight
+            )
+            .unwrap();
+        }
+    }
+
+    let mut positions = positions.iter();
+    for (column, layer) in layers.iter().enumerate() {
+        writeln!(
+            svg,
+            "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-weight=\"bold\">{}</text>",
+            MARGIN + COLUMN_SPACING * column as f32,
+            MARGIN / 2.0,
+            escape(&layer.label)
+        )
+        .unwrap();
+
+        for (label, (x, y)) in layer.nodes.iter().zip(&mut positions) {
+            writeln!(
+                svg,
+                "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"white\" stroke=\"black\"/>",
+                x, y, NODE_RADIUS
+            )
+            .unwrap();
+            writeln!(
+                svg,
+                "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+                x,
+                y,
+                escape(label)
+            )
+            .unwrap();
+        }
+    }
+
+    writeln!(svg, "</svg>").unwrap();
+    svg
+}
+
+fn max_weight(edges: &[GraphEdge]) -> f32 {
+    edges
+        .iter()
+        .map(|edge| edge.weight.abs())
+        .fold(0.0, f32::max)
+}
+
+fn color(weight: f32) -> &'static str {
+    if weight < 0.0 {
+        NEGATIVE
+    } else {
+        POSITIVE
+    }
+}
+
+/// Edge thickness proportional to the weight's magnitude
+fn edge_width(weight: f32, max_weight: f32) -> f32 {
+    if max_weight > 0.0 {
+        MIN_EDGE_WIDTH + (MAX_EDGE_WIDTH - MIN_EDGE_WIDTH) * weight.abs() / max_weight
+    } else {
+        MIN_EDGE_WIDTH
+    }
+}
+
+/// Escapes labels for both DOT strings and SVG text
+fn escape(label: &str) -> String {
+    label
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(kind: LayerKind) -> Network {
+        let layers = [
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::None,
+                kind: LayerKind::Dense,
+                initializer: Initializer::Uniform,
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::ReLU,
+                kind,
+                initializer: Initializer::Uniform,
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::Tanh,
+                kind: LayerKind::Dense,
+                initializer: Initializer::Uniform,
+            },
+        ];
+        let weights = (0..Network::parameters(&layers)).map(|n| n as f32 - 10.0);
+
+        Network::from_weights(&layers, weights)
+    }
+
+    mod layers {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let layers = network(LayerKind::Gru).layers();
+
+            assert_eq!(layers[0].label, "inputs");
+            assert_eq!(layers[0].nodes, vec!["0", "1"]);
+            assert_eq!(layers[1].label, "1: gru relu");
+            // Biases of the candidate neurons, past the update and reset gates
+            assert_eq!(layers[1].nodes, vec!["+10.00", "+15.00"]);
+            assert_eq!(layers[2].label, "2: dense tanh");
+        }
+    }
+
+    mod edges {
+        use super::*;
+
+        #[test]
+        fn test_dense() {
+            let edges = network(LayerKind::Dense).edges();
+
+            assert_eq!(edges.len(), 2 * 2 + 2);
+            assert_eq!(
+                edges[0],
+                GraphEdge {
+                    from: 0,
+                    to: 2,
+                    weight: -9.0
+                }
+            );
+            assert_eq!(
+                edges[5],
+                GraphEdge {
+                    from: 3,
+                    to: 4,
+                    weight: -2.0
+                }
+            );
+        }
+
+        #[test]
+        fn test_recurrent() {
+            let edges = network(LayerKind::Elman).edges();
+            let recurrent: Vec<_> = edges
+                .iter()
+                .filter(|edge| edge.from >= 2 && edge.to < 4)
+                .map(|edge| (edge.from, edge.to))
+                .collect();
+
+            assert_eq!(recurrent, vec![(2, 2), (3, 2), (2, 3), (3, 3)]);
+        }
+    }
+
+    mod to_dot {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let dot = network(LayerKind::Dense).to_dot();
+
+            assert!(dot.starts_with("digraph network {"));
+            assert!(dot.contains("label=\"1: dense relu\";"));
+            // The largest weight gets the thickest edge
+            assert!(dot.contains("n0 -> n2 [color=\"#b2182b\", penwidth=4.00"));
+            assert!(dot.trim_end().ends_with('}'));
+        }
+    }
+
+    mod to_svg {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let svg = network(LayerKind::Elman).to_svg();
+
+            assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+            assert_eq!(svg.matches("<circle").count(), 5);
+            assert_eq!(svg.matches("<line").count(), 4 + 2);
+            assert_eq!(svg.matches("<path").count(), 4);
+            assert!(svg.contains(">1: elman relu</text>"));
+            assert!(svg.trim_end().ends_with("</svg>"));
+        }
+
+        #[test]
+        fn test_escapes_labels() {
+            struct Labelled;
+
+            impl Graph for Labelled {
+                fn layers(&self) -> Vec<GraphLayer> {
+                    vec![GraphLayer {
+                        label: "<a & b>".to_string(),
+                        nodes: vec!["\"x\"".to_string()],
+                    }]
+                }
+
+                fn edges(&self) -> Vec<GraphEdge> {
+                    Vec::new()
+                }
+            }
+
+            let svg = Labelled.to_svg();
+            assert!(svg.contains("&lt;a &amp; b&gt;"));
+            assert!(svg.contains("&quot;x&quot;"));
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::*;
+
+/// Plastic weights are kept within ±WEIGHT_LIMIT, so that runaway Hebbian
+/// feedback can't saturate the network
+const WEIGHT_LIMIT: f32 = 4.0;
+
+/// Generalised "ABCD" Hebbian learning rule, updating each weight by
+///
+/// Δw = η·m·(A·pre·post + B·pre + C·post + D)
+///
+/// where pre and post are the activities on either side of the connection
+/// and m is a modulating signal, e.g. a reward
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HebbianRule {
+    pub learning_rate: f32,
+    pub a: f32, // Correlation term
+    pub b: f32, // Presynaptic term
+    pub c: f32, // Postsynaptic term
+    pub d: f32, // Constant drift
+}
+
+impl HebbianRule {
+    /// Number of evolvable coefficients
+    pub const PARAMETERS: usize = 5;
+
+    pub fn random(rng: &mut dyn rand::RngCore) -> HebbianRule {
+        HebbianRule {
+            learning_rate: rng.gen_range(0.0..=0.1),
+            a: rng.gen_range(-1.0..=1.0),
+            b: rng.gen_range(-1.0..=1.0),
+            c: rng.gen_range(-1.0..=1.0),
+            d: rng.gen_range(-1.0..=1.0),
+        }
+    }
+
+    /// Learning rate, followed by the A, B, C and D coefficients
+    pub fn weights(&self) -> impl Iterator<Item = f32> {
+        vec![self.learning_rate, self.a, self.b, self.c, self.d].into_iter()
+    }
+
+    pub fn from_weights(weights: &mut dyn Iterator<Item = f32>) -> HebbianRule {
+        let mut next = || weights.next().expect("Not enough weights!");
+
+        HebbianRule {
+            learning_rate: next(),
+            a: next(),
+            b: next(),
+            c: next(),
+            d: next(),
+        }
+    }
+
+    /// Weight of a connection after a single update
+    pub fn update(&self, weight: f32, pre: f32, post: f32, modulation: f32) -> f32 {
+        let delta = self.learning_rate
+            * modulation
+            * (self.a * pre * post + self.b * pre + self.c * post + self.d);
+
+        (weight + delta).clamp(-WEIGHT_LIMIT, WEIGHT_LIMIT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    const RULE: HebbianRule = HebbianRule {
+        learning_rate: 0.5,
+        a: 1.0,
+        b: 0.5,
+        c: -0.25,
+        d: 0.1,
+    };
+
+    #[test]
+    fn test_update() {
+        // 0.5 * 2.0 * (1.0 * 0.5 * -1.0 + 0.5 * 0.5 - 0.25 * -1.0 + 0.1) = 0.1
+        assert_relative_eq!(RULE.update(0.2, 0.5, -1.0, 2.0), 0.3);
+    }
+
+    #[test]
+    fn test_update_without_modulation() {
+        assert_relative_eq!(RULE.update(0.2, 0.5, -1.0, 0.0), 0.2);
+    }
+
+    #[test]
+    fn test_update_is_clamped() {
+        assert_relative_eq!(RULE.update(3.9, 1.0, 1.0, 10.0), WEIGHT_LIMIT);
+    }
+
+    #[test]
+    fn test_from_weights() {
+        let weights: Vec<_> = RULE.weights().collect();
+        let rule = HebbianRule::from_weights(&mut weights.into_iter());
+
+        assert_eq!(rule, RULE);
+    }
+}
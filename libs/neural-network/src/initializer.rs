@@ -0,0 +1,194 @@
+use std::f32::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::*;
+
+/// How `Network::random` draws the weights and biases of a layer. Apart
+/// from `Uniform`, every initializer scales weights by the number of inputs
+/// feeding each neuron and starts biases at zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Initializer {
+    /// Weights and biases drawn from -1..=1, regardless of fan-in
+    #[default]
+    Uniform,
+    /// Glorot & Bengio, uniform within ±√(6 / (fan-in + fan-out)); suits tanh and sigmoid
+    Xavier,
+    /// He et al., normal with σ = √(2 / fan-in); suits ReLU
+    He,
+    /// LeCun et al., normal with σ = √(1 / fan-in)
+    LeCun,
+    /// Normal with the given σ
+    Normal(f32),
+}
+
+impl Initializer {
+    pub(crate) fn weight(&self, rng: &mut dyn rand::RngCore, fan_in: usize, fan_out: usize) -> f32 {
+        let fan_in = fan_in.max(1) as f32;
+        let fan_out = fan_out as f32;
+
+        match *self {
+            Initializer::Uniform => rng.gen_range(-1.0..=1.0),
+            Initializer::Xavier => {
+                let limit = (6.0 / (fan_in + fan_out)).sqrt();
+                rng.gen_range(-limit..=limit)
+            }
+            Initializer::He => (2.0 / fan_in).sqrt() * normal(rng),
+            Initializer::LeCun => (1.0 / fan_in).sqrt() * normal(rng),
+            Initializer::Normal(sigma) => sigma * normal(rng),
+        }
+    }
+
+    pub(crate) fn bias(&self, rng: &mut dyn rand::RngCore) -> f32 {
+        match *self {
+            Initializer::Uniform => rng.gen_range(-1.0..=1.0),
+            _ => 0.0,
+        }
+    }
+}
+
+impl fmt::Display for Initializer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match *self {
+            Initializer::Uniform => "uniform",
+            Initializer::Xavier => "xavier",
+            Initializer::He => "he",
+            Initializer::LeCun => "lecun",
+            Initializer::Normal(sigma) => return write!(f, "normal({})", sigma),
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// Parses an initializer's name, with the σ of `normal` in parentheses,
+/// e.g. `normal(0.1)`
+impl FromStr for Initializer {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Initializer, String> {
+        let lowercase = name.trim().to_ascii_lowercase();
+
+        if let Some(sigma) = lowercase
+            .strip_prefix("normal(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            return sigma
+                .trim()
+                .parse()
+                .map(Initializer::Normal)
+                .map_err(|_| format!("Invalid initializer parameter: {}", name));
+        }
+
+        match lowercase.as_str() {
+            "uniform" => Ok(Initializer::Uniform),
+            "xavier" | "glorot" => Ok(Initializer::Xavier),
+            "he" => Ok(Initializer::He),
+            "lecun" => Ok(Initializer::LeCun),
+            _ => Err(format!("Unknown initializer: {}", name)),
+        }
+    }
+}
+
+/// Standard normal sample, through the Box-Muller transform
+fn normal(rng: &mut dyn rand::RngCore) -> f32 {
+    // Excludes zero, whose logarithm is infinite
+    let u1 = 1.0 - rng.gen::<f32>();
+    let u2 = rng.gen::<f32>();
+
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    /// Mean and standard deviation of many weights
+    fn statistics(initializer: Initializer, fan_in: usize, fan_out: usize) -> (f32, f32) {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let weights: Vec<_> = (0..10_000)
+            .map(|_| initializer.weight(&mut rng, fan_in, fan_out))
+            .collect();
+
+        let mean = weights.iter().sum::<f32>() / weights.len() as f32;
+        let variance =
+            weights.iter().map(|w| (w - mean).powi(2)).sum::<f32>() / weights.len() as f32;
+
+        (mean, variance.sqrt())
+    }
+
+    mod weight {
+        use super::*;
+        use approx::assert_abs_diff_eq;
+
+        #[test]
+        fn test_xavier() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let limit = (6.0f32 / 150.0).sqrt();
+
+            assert!((0..1000)
+                .map(|_| Initializer::Xavier.weight(&mut rng, 100, 50))
+                .all(|weight| weight.abs() <= limit));
+        }
+
+        #[test]
+        fn test_he() {
+            let (mean, sigma) = statistics(Initializer::He, 50, 10);
+
+            assert_abs_diff_eq!(mean, 0.0, epsilon = 0.01);
+            assert_abs_diff_eq!(sigma, 0.2, epsilon = 0.01);
+        }
+
+        #[test]
+        fn test_lecun() {
+            let (_, sigma) = statistics(Initializer::LeCun, 100, 10);
+
+            assert_abs_diff_eq!(sigma, 0.1, epsilon = 0.005);
+        }
+
+        #[test]
+        fn test_normal() {
+            let (mean, sigma) = statistics(Initializer::Normal(0.5), 1, 1);
+
+            assert_abs_diff_eq!(mean, 0.0, epsilon = 0.02);
+            assert_abs_diff_eq!(sigma, 0.5, epsilon = 0.02);
+        }
+    }
+
+    mod bias {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            assert_ne!(Initializer::Uniform.bias(&mut rng), 0.0);
+            assert_eq!(Initializer::He.bias(&mut rng), 0.0);
+        }
+    }
+
+    mod from_str {
+        use super::*;
+
+        #[test]
+        fn test_round_trip() {
+            for initializer in [
+                Initializer::Uniform,
+                Initializer::Xavier,
+                Initializer::He,
+                Initializer::LeCun,
+                Initializer::Normal(0.1),
+            ] {
+                assert_eq!(initializer.to_string().parse(), Ok(initializer));
+            }
+        }
+
+        #[test]
+        fn test_invalid() {
+            assert!("orthogonal".parse::<Initializer>().is_err());
+            assert!("normal(wide)".parse::<Initializer>().is_err());
+        }
+    }
+}
@@ -1,9 +1,13 @@
 use crate::*;
 
+/// Recurrent layers see their previous outputs after their inputs, and GRU
+/// layers store their neurons as update gates, then reset gates, then candidates
 #[derive(Debug, Clone)]
 pub(crate) struct Layer {
     pub(crate) neurons: Vec<Neuron>,
     pub(crate) activation: Activation,
+    pub(crate) kind: LayerKind,
+    pub(crate) state: Vec<f32>,
 }
 
 impl Layer {
@@ -12,24 +16,156 @@ impl Layer {
         input_neurons: usize,
         output_neurons: usize,
         activation: Activation,
+        kind: LayerKind,
+        initializer: Initializer,
     ) -> Layer {
-        let neurons = (0..output_neurons)
-            .map(|_| Neuron::random(rng, input_neurons))
+        let neurons = (0..kind.gates() * output_neurons)
+            .map(|_| {
+                Neuron::random(
+                    rng,
+                    Self::neuron_inputs(input_neurons, output_neurons, kind),
+                    output_neurons,
+                    initializer,
+                )
+            })
             .collect();
 
-        Layer {
-            neurons,
-            activation,
+        Layer::new(neurons, output_neurons, activation, kind)
+    }
+
+    pub(crate) fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32> {
+        match self.kind {
+            LayerKind::Dense => {
+                let outputs = self
+                    .neurons
+                    .iter()
+                    .map(|neuron| neuron.propagate(&inputs))
+                    .collect();
+                self.activation.apply(outputs)
+            }
+            LayerKind::Elman => {
+                let inputs: Vec<_> = inputs.into_iter().chain(self.state.clone()).collect();
+                let outputs = self
+                    .neurons
+                    .iter()
+                    .map(|neuron| neuron.propagate(&inputs))
+                    .collect();
+
+                self.state = self.activation.apply(outputs);
+                self.state.clone()
+            }
+            LayerKind::Gru => {
+                let size = self.state.len();
+                let (gates, candidates) = self.neurons.split_at(2 * size);
+
+                let combined: Vec<_> = inputs.iter().chain(&self.state).copied().collect();
+                let gates = Activation::Sigmoid.apply(
+                    gates
+                        .iter()
+                        .map(|neuron| neuron.propagate(&combined))
+                        .collect(),
+                );
+                let (update, reset) = gates.split_at(size);
+
+                let reset_state = self.state.iter().zip(reset).map(|(h, r)| h * r);
+                let combined: Vec<_> = inputs.into_iter().chain(reset_state).collect();
+                let candidates = self.activation.apply(
+                    candidates
+                        .iter()
+                        .map(|neuron| neuron.propagate(&combined))
+                        .collect(),
+                );
+
+                for ((h, z), n) in self.state.iter_mut().zip(update).zip(candidates) {
+                    *h = (1.0 - z) * n + z * *h;
+                }
+
+                self.state.clone()
+            }
         }
     }
 
-    pub(crate) fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
-        let outputs = self
+    /// Values seen by each neuron of a dense or Elman layer, given the layer's inputs
+    pub(crate) fn presynaptic(&self, inputs: &[f32]) -> Vec<f32> {
+        inputs.iter().chain(&self.state).copied().collect()
+    }
+
+    /// Neurons whose activations become the layer's outputs, which for GRU
+    /// layers are the candidates
+    pub(crate) fn outputs(&self) -> &[Neuron] {
+        let gates = self.kind.gates();
+        &self.neurons[self.neurons.len() / gates * (gates - 1)..]
+    }
+
+    /// Weighted sums of a dense layer's neurons, before the activation
+    pub(crate) fn preactivations(&self, inputs: &[f32]) -> Vec<f32> {
+        self.neurons
+            .iter()
+            .map(|neuron| neuron.propagate(inputs))
+            .collect()
+    }
+
+    /// Backpropagates the loss gradient of a dense layer's outputs, adding
+    /// the gradient of each bias and weight to `parameters`, ordered like
+    /// `Network::weights`, and returning the gradient of the layer's inputs
+    pub(crate) fn backpropagate(
+        &self,
+        inputs: &[f32],
+        preactivations: &[f32],
+        outputs: &[f32],
+        gradients: &[f32],
+        parameters: &mut [f32],
+    ) -> Vec<f32> {
+        let deltas = self
+            .activation
+            .backpropagate(preactivations, outputs, gradients);
+        let mut input_gradients = vec![0.0; inputs.len()];
+
+        for ((neuron, delta), parameters) in self
             .neurons
             .iter()
-            .map(|neuron| neuron.propagate(&inputs))
-            .collect();
-        self.activation.apply(outputs)
+            .zip(&deltas)
+            .zip(parameters.chunks_mut(inputs.len() + 1))
+        {
+            parameters[0] += delta;
+
+            for ((parameter, input), (input_gradient, weight)) in parameters[1..]
+                .iter_mut()
+                .zip(inputs)
+                .zip(input_gradients.iter_mut().zip(&neuron.weights))
+            {
+                *parameter += delta * input;
+                *input_gradient += delta * weight;
+            }
+        }
+
+        input_gradients
+    }
+
+    /// Applies the Hebbian `rule` to every connection of a dense or Elman
+    /// layer. GRU gates don't map onto a single pair of activities, so GRU
+    /// layers stay fixed.
+    pub(crate) fn learn(
+        &mut self,
+        presynaptic: &[f32],
+        outputs: &[f32],
+        rule: &HebbianRule,
+        modulation: f32,
+    ) {
+        if self.kind == LayerKind::Gru {
+            return;
+        }
+
+        for (neuron, post) in self.neurons.iter_mut().zip(outputs) {
+            for (weight, pre) in neuron.weights.iter_mut().zip(presynaptic) {
+                *weight = rule.update(*weight, *pre, *post, modulation);
+            }
+        }
+    }
+
+    /// Clears the memory of recurrent layers
+    pub(crate) fn reset(&mut self) {
+        self.state.iter_mut().for_each(|h| *h = 0.0);
     }
 
     pub fn from_weights(
@@ -37,14 +173,42 @@ impl Layer {
         output_size: usize,
         weights: &mut dyn Iterator<Item = f32>,
         activation: Activation,
+        kind: LayerKind,
     ) -> Layer {
-        let neurons = (0..output_size)
-            .map(|_| Neuron::from_weights(input_size, weights))
+        let neurons = (0..kind.gates() * output_size)
+            .map(|_| {
+                Neuron::from_weights(Self::neuron_inputs(input_size, output_size, kind), weights)
+            })
             .collect();
 
+        Layer::new(neurons, output_size, activation, kind)
+    }
+
+    fn new(
+        neurons: Vec<Neuron>,
+        output_size: usize,
+        activation: Activation,
+        kind: LayerKind,
+    ) -> Layer {
+        let state = if kind.is_recurrent() {
+            vec![0.0; output_size]
+        } else {
+            Vec::new()
+        };
+
         Layer {
             neurons,
             activation,
+            kind,
+            state,
+        }
+    }
+
+    fn neuron_inputs(input_size: usize, output_size: usize, kind: LayerKind) -> usize {
+        if kind.is_recurrent() {
+            input_size + output_size
+        } else {
+            input_size
         }
     }
 }
@@ -62,7 +226,14 @@ mod tests {
         #[test]
         fn test() {
             let mut rng = ChaCha8Rng::from_seed(Default::default());
-            let layer = Layer::random(&mut rng, 2, 2, Activation::None);
+            let layer = Layer::random(
+                &mut rng,
+                2,
+                2,
+                Activation::None,
+                LayerKind::Dense,
+                Initializer::Uniform,
+            );
 
             assert_relative_eq!(layer.neurons[0].bias, 0.8181262);
             assert_relative_eq!(
@@ -84,7 +255,7 @@ mod tests {
 
         #[test]
         fn test() {
-            let layer = Layer {
+            let mut layer = Layer {
                 neurons: vec![
                     Neuron {
                         weights: vec![0.25, 0.75],
@@ -96,6 +267,8 @@ mod tests {
                     },
                 ],
                 activation: Activation::None,
+                kind: LayerKind::Dense,
+                state: Vec::new(),
             };
 
             let prop = layer.propagate(vec![0.3, 0.6]);
@@ -104,7 +277,7 @@ mod tests {
 
         #[test]
         fn test_activation_functions() {
-            let relu_layer = Layer {
+            let mut relu_layer = Layer {
                 neurons: vec![
                     Neuron {
                         weights: vec![0.25, 0.75],
@@ -116,11 +289,13 @@ mod tests {
                     },
                 ],
                 activation: Activation::ReLU,
+                kind: LayerKind::Dense,
+                state: Vec::new(),
             };
             let prop = relu_layer.propagate(vec![0.3, 0.6]);
             assert_relative_eq!(prop.as_slice(), [0.525, 0.0].as_ref());
 
-            let sigmoid_layer = Layer {
+            let mut sigmoid_layer = Layer {
                 neurons: vec![
                     Neuron {
                         weights: vec![0.25, 0.75],
@@ -132,11 +307,13 @@ mod tests {
                     },
                 ],
                 activation: Activation::Sigmoid,
+                kind: LayerKind::Dense,
+                state: Vec::new(),
             };
             let prop = sigmoid_layer.propagate(vec![0.3, 0.6]);
             assert_relative_eq!(prop.as_slice(), [0.82127357, 0.34298956].as_ref());
 
-            let softmax_layer = Layer {
+            let mut softmax_layer = Layer {
                 neurons: vec![
                     Neuron {
                         weights: vec![0.25, 0.75],
@@ -148,10 +325,57 @@ mod tests {
                     },
                 ],
                 activation: Activation::Softmax,
+                kind: LayerKind::Dense,
+                state: Vec::new(),
             };
             let prop = softmax_layer.propagate(vec![0.3, 0.6]);
             assert_relative_eq!(prop.iter().sum::<f32>(), 1.0);
             assert_relative_eq!(prop.as_slice(), [0.3953209, 0.60467905].as_ref());
         }
+
+        #[test]
+        fn test_elman() {
+            let mut layer = Layer::new(
+                vec![Neuron {
+                    weights: vec![1.0, 0.5],
+                    bias: 0.0,
+                }],
+                1,
+                Activation::None,
+                LayerKind::Elman,
+            );
+
+            assert_relative_eq!(layer.propagate(vec![1.0])[0], 1.0);
+            assert_relative_eq!(layer.propagate(vec![1.0])[0], 1.5);
+            assert_relative_eq!(layer.propagate(vec![0.0])[0], 0.75);
+
+            layer.reset();
+            assert_relative_eq!(layer.propagate(vec![1.0])[0], 1.0);
+        }
+
+        #[test]
+        fn test_gru() {
+            let gate = Neuron {
+                weights: vec![0.0, 0.0],
+                bias: 0.0,
+            };
+            let candidate = Neuron {
+                weights: vec![1.0, 1.0],
+                bias: 0.0,
+            };
+            let mut layer = Layer::new(
+                vec![gate.clone(), gate, candidate],
+                1,
+                Activation::None,
+                LayerKind::Gru,
+            );
+
+            // Both gates are held at 0.5, so the state moves halfway to each candidate
+            assert_relative_eq!(layer.propagate(vec![1.0])[0], 0.5);
+            assert_relative_eq!(layer.propagate(vec![1.0])[0], 0.875);
+
+            layer.reset();
+            assert_relative_eq!(layer.propagate(vec![1.0])[0], 0.5);
+        }
     }
 }
@@ -1,6 +1,75 @@
+use std::fmt;
+use std::str::FromStr;
+
 use crate::*;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct LayerTopology {
     pub neurons: usize,
     pub activation: Activation,
+    pub kind: LayerKind,
+    pub initializer: Initializer, // Unused by the input layer
+}
+
+impl LayerTopology {
+    /// Number of weights and biases needed to feed this layer from `inputs` neurons
+    pub fn parameters(&self, inputs: usize) -> usize {
+        let inputs = if self.kind.is_recurrent() {
+            inputs + self.neurons
+        } else {
+            inputs
+        };
+
+        self.kind.gates() * self.neurons * (inputs + 1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LayerKind {
+    /// Stateless, fully connected layer
+    #[default]
+    Dense,
+    /// Fully connected layer also fed its own previous outputs
+    Elman,
+    /// Gated recurrent unit, with update and reset gates controlling its memory
+    Gru,
+}
+
+impl LayerKind {
+    /// Number of neurons needed per output, one per gate
+    pub(crate) fn gates(&self) -> usize {
+        match *self {
+            LayerKind::Dense | LayerKind::Elman => 1,
+            LayerKind::Gru => 3,
+        }
+    }
+
+    pub fn is_recurrent(&self) -> bool {
+        *self != LayerKind::Dense
+    }
+}
+
+impl fmt::Display for LayerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match *self {
+            LayerKind::Dense => "dense",
+            LayerKind::Elman => "elman",
+            LayerKind::Gru => "gru",
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl FromStr for LayerKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<LayerKind, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "dense" => Ok(LayerKind::Dense),
+            "elman" => Ok(LayerKind::Elman),
+            "gru" => Ok(LayerKind::Gru),
+            _ => Err(format!("Unknown layer kind: {}", name)),
+        }
+    }
 }
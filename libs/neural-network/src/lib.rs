@@ -1,10 +1,26 @@
 use rand::Rng;
 
-pub use self::{activation::*, layer_topology::*, network::*};
+pub use self::{
+    activation::*, ctrnn::*, drift_report::*, graph::*, hebbian_rule::*, initializer::*,
+    layer_topology::*, matrix_network::*, network::*, plastic_network::*, propagate::*,
+    quantized_network::*, sparse_network::*, training::*,
+};
 use self::{layer::*, neuron::*};
 
 mod activation;
+mod ctrnn;
+mod drift_report;
+mod graph;
+mod hebbian_rule;
+mod initializer;
 mod layer;
 mod layer_topology;
+mod matrix_network;
 mod network;
 mod neuron;
+mod plastic_network;
+mod propagate;
+mod quantized_network;
+mod simd;
+mod sparse_network;
+mod training;
@@ -0,0 +1,471 @@
+use crate::*;
+
+/// Network storing each layer as a single row-major weight matrix, with the
+/// bias of each neuron in the first column. Propagating through `Scratch`
+/// buffers doesn't allocate once they have grown, which makes it the faster
+/// choice when many networks are evaluated every tick.
+///
+/// Weights are laid out exactly like those of `Network`, so both can be
+/// built from the same chromosome.
+#[derive(Debug, Clone)]
+pub struct MatrixNetwork {
+    layers: Vec<MatrixLayer>,
+}
+
+#[derive(Debug, Clone)]
+struct MatrixLayer {
+    inputs: usize,
+    neurons: usize,
+    activation: Activation,
+    kind: LayerKind,
+    weights: Vec<f32>,
+    state: Vec<f32>,
+}
+
+/// Buffers reused between propagations, so that they don't allocate
+#[derive(Debug, Clone, Default)]
+pub struct Scratch {
+    current: Vec<f32>,
+    next: Vec<f32>,
+    combined: Vec<f32>,
+    gates: Vec<f32>,
+}
+
+impl MatrixNetwork {
+    pub fn random(rng: &mut dyn rand::RngCore, layers: &[LayerTopology]) -> MatrixNetwork {
+        MatrixNetwork::from(&Network::random(rng, layers))
+    }
+
+    pub fn from_weights(
+        layers: &[LayerTopology],
+        weights: impl IntoIterator<Item = f32>,
+    ) -> MatrixNetwork {
+        assert!(layers.len() > 1);
+
+        let mut weights = weights.into_iter();
+        let layers = layers
+            .windows(2)
+            .map(|layers| {
+                let parameters = layers[1].parameters(layers[0].neurons);
+                let weights: Vec<_> = (&mut weights).take(parameters).collect();
+                assert_eq!(weights.len(), parameters, "Not enough weights!");
+
+                MatrixLayer {
+                    inputs: layers[0].neurons,
+                    neurons: layers[1].neurons,
+                    activation: layers[1].activation,
+                    kind: layers[1].kind,
+                    weights,
+                    state: if layers[1].kind.is_recurrent() {
+                        vec![0.0; layers[1].neurons]
+                    } else {
+                        Vec::new()
+                    },
+                }
+            })
+            .collect();
+
+        if weights.next().is_some() {
+            panic!("Too many weights!");
+        }
+
+        MatrixNetwork { layers }
+    }
+
+    pub fn weights(&self) -> impl Iterator<Item = f32> + '_ {
+        self.layers
+            .iter()
+            .flat_map(|layer| layer.weights.iter().copied())
+    }
+
+    pub fn topology(&self) -> Vec<LayerTopology> {
+        use std::iter::once;
+
+        let inputs = LayerTopology {
+            // Activation of the input layer is unused
+            neurons: self.inputs(),
+            activation: Activation::None,
+            kind: LayerKind::Dense,
+            initializer: Initializer::default(),
+        };
+
+        once(inputs)
+            .chain(self.layers.iter().map(|layer| LayerTopology {
+                neurons: layer.neurons,
+                activation: layer.activation,
+                kind: layer.kind,
+                // Only matters to `Network::random`, so it isn't remembered
+                initializer: Initializer::default(),
+            }))
+            .collect()
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.layers[0].inputs
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.layers[self.layers.len() - 1].neurons
+    }
+
+    pub fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32> {
+        self.propagate_with(&inputs, &mut Scratch::default())
+            .to_vec()
+    }
+
+    /// Feeds the inputs through every layer using the given buffers,
+    /// returning the outputs held by them
+    pub fn propagate_with<'s>(&mut self, inputs: &[f32], scratch: &'s mut Scratch) -> &'s [f32] {
+        assert_eq!(inputs.len(), self.inputs());
+
+        scratch.current.clear();
+        scratch.current.extend_from_slice(inputs);
+
+        for layer in &mut self.layers {
+            layer.propagate(
+                &scratch.current,
+                &mut scratch.next,
+                &mut scratch.combined,
+                &mut scratch.gates,
+            );
+            std::mem::swap(&mut scratch.current, &mut scratch.next);
+        }
+
+        &scratch.current
+    }
+
+    /// Propagates like `propagate`, returning the inputs followed by the
+    /// outputs of every layer, the last of which are the network's outputs
+    pub fn trace(&mut self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        assert_eq!(inputs.len(), self.inputs());
+
+        let mut scratch = Scratch::default();
+        let mut trace = Vec::with_capacity(self.layers.len() + 1);
+        trace.push(inputs);
+
+        for layer in &mut self.layers {
+            let mut outputs = Vec::new();
+            layer.propagate(
+                &trace[trace.len() - 1],
+                &mut outputs,
+                &mut scratch.combined,
+                &mut scratch.gates,
+            );
+            trace.push(outputs);
+        }
+
+        trace
+    }
+
+    /// Propagates networks one after another, reusing the same scratch
+    /// buffers instead of allocating for each; the networks aren't evaluated
+    /// together as a single batched product. `inputs` holds the inputs of
+    /// each network back to back, and `outputs` is filled with their outputs
+    /// in the same way.
+    pub fn propagate_batch<'a>(
+        networks: impl IntoIterator<Item = &'a mut MatrixNetwork>,
+        inputs: &[f32],
+        outputs: &mut Vec<f32>,
+        scratch: &mut Scratch,
+    ) {
+        outputs.clear();
+
+        let mut offset = 0;
+        for network in networks {
+            let end = offset + network.inputs();
+            outputs.extend_from_slice(network.propagate_with(&inputs[offset..end], scratch));
+            offset = end;
+        }
+
+        assert_eq!(offset, inputs.len(), "Inputs left over after the batch");
+    }
+
+    /// Clears the memory of every recurrent layer
+    pub fn reset(&mut self) {
+        for layer in &mut self.layers {
+            layer.state.iter_mut().for_each(|h| *h = 0.0);
+        }
+    }
+}
+
+impl From<&Network> for MatrixNetwork {
+    fn from(network: &Network) -> MatrixNetwork {
+        MatrixNetwork::from_weights(&network.topology(), network.weights())
+    }
+}
+
+/// Rebuilds the per-neuron layout, e.g. to prune, quantize or train the
+/// network; the memory of recurrent layers isn't carried over
+impl From<&MatrixNetwork> for Network {
+    fn from(network: &MatrixNetwork) -> Network {
+        Network::from_weights(&network.topology(), network.weights())
+    }
+}
+
+impl Propagate for MatrixNetwork {
+    fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32> {
+        MatrixNetwork::propagate(self, inputs)
+    }
+
+    fn trace(&mut self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        MatrixNetwork::trace(self, inputs)
+    }
+
+    fn reset(&mut self) {
+        MatrixNetwork::reset(self)
+    }
+}
+
+impl MatrixLayer {
+    /// Same as `Layer::propagate`, with recurrent inputs gathered in `combined`
+    /// and GRU gates in `gates`
+    fn propagate(
+        &mut self,
+        inputs: &[f32],
+        outputs: &mut Vec<f32>,
+        combined: &mut Vec<f32>,
+        gates: &mut Vec<f32>,
+    ) {
+        let columns = self.columns();
+
+        match self.kind {
+            LayerKind::Dense => {
+                affine(&self.weights, columns, inputs, outputs);
+                self.activation.apply_in_place(outputs);
+            }
+            LayerKind::Elman => {
+                combined.clear();
+                combined.extend_from_slice(inputs);
+                combined.extend_from_slice(&self.state);
+
+                affine(&self.weights, columns, combined, outputs);
+                self.activation.apply_in_place(outputs);
+                self.state.copy_from_slice(outputs);
+            }
+            LayerKind::Gru => {
+                let size = self.neurons;
+                let (gate_weights, candidate_weights) = self.weights.split_at(2 * size * columns);
+
+                combined.clear();
+                combined.extend_from_slice(inputs);
+                combined.extend_from_slice(&self.state);
+
+                affine(gate_weights, columns, combined, gates);
+                Activation::Sigmoid.apply_in_place(gates);
+                let (update, reset) = gates.split_at(size);
+
+                combined.truncate(inputs.len());
+                combined.extend(self.state.iter().zip(reset).map(|(h, r)| h * r));
+
+                affine(candidate_weights, columns, combined, outputs);
+                self.activation.apply_in_place(outputs);
+
+                for ((h, z), n) in self.state.iter_mut().zip(update).zip(outputs.iter_mut()) {
+                    *h = (1.0 - z) * *n + z * *h;
+                    *n = *h;
+                }
+            }
+        }
+    }
+
+    /// Bias, followed by a weight per input and, for recurrent layers, per neuron
+    fn columns(&self) -> usize {
+        if self.kind.is_recurrent() {
+            1 + self.inputs + self.neurons
+        } else {
+            1 + self.inputs
+        }
+    }
+}
+
+/// Replaces `outputs` with the product of the weights and the inputs, plus the biases
+fn affine(weights: &[f32], columns: usize, inputs: &[f32], outputs: &mut Vec<f32>) {
+    outputs.clear();
+    outputs.extend(
+        weights
+            .chunks_exact(columns)
+            .map(|row| simd::dot(&row[1..], inputs) + row[0]),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn topology(kind: LayerKind) -> Vec<LayerTopology> {
+        vec![
+            LayerTopology {
+                neurons: 3,
+                activation: Activation::None,
+                kind: LayerKind::Dense,
+                initializer: Initializer::Uniform,
+            },
+            LayerTopology {
+                neurons: 4,
+                activation: Activation::ReLU,
+                kind,
+                initializer: Initializer::Uniform,
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Tanh,
+                kind: LayerKind::Dense,
+                initializer: Initializer::Uniform,
+            },
+        ]
+    }
+
+    mod from_weights {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let layers = topology(LayerKind::Gru);
+            let weights: Vec<_> = (0..Network::parameters(&layers))
+                .map(|n| n as f32 / 100.0)
+                .collect();
+            let network = MatrixNetwork::from_weights(&layers, weights.clone());
+            let actual: Vec<_> = network.weights().collect();
+
+            assert_relative_eq!(actual.as_slice(), weights.as_slice());
+            assert_eq!(network.inputs(), 3);
+            assert_eq!(network.outputs(), 2);
+        }
+
+        #[test]
+        #[should_panic(expected = "Not enough weights!")]
+        fn test_not_enough_weights() {
+            MatrixNetwork::from_weights(&topology(LayerKind::Dense), vec![0.0; 10]);
+        }
+
+        #[test]
+        #[should_panic(expected = "Too many weights!")]
+        fn test_too_many_weights() {
+            let layers = topology(LayerKind::Dense);
+            MatrixNetwork::from_weights(&layers, vec![0.0; Network::parameters(&layers) + 1]);
+        }
+    }
+
+    mod into_network {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let network = Network::random(&mut rng, &topology(LayerKind::Elman));
+            let matrix = MatrixNetwork::from(&network);
+            let actual = Network::from(&matrix);
+
+            assert_eq!(actual.topology(), network.topology());
+            assert_eq!(
+                actual.weights().collect::<Vec<_>>(),
+                network.weights().collect::<Vec<_>>()
+            );
+        }
+    }
+
+    mod propagate {
+        use super::*;
+
+        fn assert_matches_network(kind: LayerKind) {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut network = Network::random(&mut rng, &topology(kind));
+            let mut matrix = MatrixNetwork::from(&network);
+            let mut scratch = Scratch::default();
+
+            for step in 0..5 {
+                let inputs = vec![0.5, -0.25 * step as f32, 1.0];
+                let expected = network.propagate(inputs.clone());
+                let actual = matrix.propagate_with(&inputs, &mut scratch);
+
+                assert_relative_eq!(actual, expected.as_slice());
+            }
+
+            network.reset();
+            matrix.reset();
+            let expected = network.propagate(vec![1.0; 3]);
+            let actual = matrix.propagate(vec![1.0; 3]);
+            assert_relative_eq!(actual.as_slice(), expected.as_slice());
+        }
+
+        #[test]
+        fn test_dense() {
+            assert_matches_network(LayerKind::Dense);
+        }
+
+        #[test]
+        fn test_elman() {
+            assert_matches_network(LayerKind::Elman);
+        }
+
+        #[test]
+        fn test_gru() {
+            assert_matches_network(LayerKind::Gru);
+        }
+
+        #[test]
+        fn test_batch() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut networks: Vec<_> = (0..3)
+                .map(|_| MatrixNetwork::random(&mut rng, &topology(LayerKind::Elman)))
+                .collect();
+            let inputs: Vec<_> = (0..9).map(|n| n as f32 / 10.0).collect();
+
+            let expected: Vec<_> = networks
+                .clone()
+                .iter_mut()
+                .zip(inputs.chunks(3))
+                .flat_map(|(network, inputs)| network.propagate(inputs.to_vec()))
+                .collect();
+
+            let mut outputs = Vec::new();
+            MatrixNetwork::propagate_batch(
+                &mut networks,
+                &inputs,
+                &mut outputs,
+                &mut Scratch::default(),
+            );
+
+            assert_relative_eq!(outputs.as_slice(), expected.as_slice());
+        }
+
+        #[test]
+        #[should_panic(expected = "Inputs left over after the batch")]
+        fn test_batch_with_leftover_inputs() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut network = MatrixNetwork::random(&mut rng, &topology(LayerKind::Dense));
+
+            MatrixNetwork::propagate_batch(
+                [&mut network],
+                &[0.0; 4],
+                &mut Vec::new(),
+                &mut Scratch::default(),
+            );
+        }
+    }
+
+    mod trace {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut network = Network::random(&mut rng, &topology(LayerKind::Gru));
+            let mut matrix = MatrixNetwork::from(&network);
+
+            for step in 0..3 {
+                let inputs = vec![0.5, -0.25 * step as f32, 1.0];
+                let expected = network.trace(inputs.clone());
+                let actual = matrix.trace(inputs);
+
+                assert_eq!(actual.len(), expected.len());
+                for (actual, expected) in actual.iter().zip(&expected) {
+                    assert_relative_eq!(actual.as_slice(), expected.as_slice());
+                }
+            }
+        }
+    }
+}
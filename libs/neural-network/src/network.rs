@@ -2,7 +2,7 @@ use crate::*;
 
 #[derive(Debug, Clone)]
 pub struct Network {
-    layers: Vec<Layer>,
+    pub(crate) layers: Vec<Layer>,
 }
 
 impl Network {
@@ -17,6 +17,8 @@ impl Network {
                     layers[0].neurons,
                     layers[1].neurons,
                     layers[1].activation,
+                    layers[1].kind,
+                    layers[1].initializer,
                 )
             })
             .collect();
@@ -24,12 +26,140 @@ impl Network {
         Network { layers }
     }
 
-    pub fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
+    /// Feeds the inputs through every layer, updating the memory of recurrent layers
+    pub fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32> {
         self.layers
-            .iter()
+            .iter_mut()
             .fold(inputs, |inputs, layer| layer.propagate(inputs))
     }
 
+    /// Propagates like `propagate`, returning the inputs followed by the
+    /// outputs of every layer, the last of which are the network's outputs
+    pub fn trace(&mut self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        let mut trace = Vec::with_capacity(self.layers.len() + 1);
+        trace.push(inputs);
+
+        for layer in &mut self.layers {
+            let outputs = layer.propagate(trace[trace.len() - 1].clone());
+            trace.push(outputs);
+        }
+
+        trace
+    }
+
+    /// Propagates like `propagate`, also returning what the neurons of each
+    /// layer received and emitted
+    pub(crate) fn propagate_recorded(&mut self, inputs: Vec<f32>) -> (Vec<f32>, Vec<Activity>) {
+        let mut activity = Vec::with_capacity(self.layers.len());
+
+        let outputs = self.layers.iter_mut().fold(inputs, |inputs, layer| {
+            let presynaptic = layer.presynaptic(&inputs);
+            let outputs = layer.propagate(inputs);
+
+            activity.push(Activity {
+                presynaptic,
+                outputs: outputs.clone(),
+            });
+
+            outputs
+        });
+
+        (outputs, activity)
+    }
+
+    /// Applies the Hebbian `rule` to every layer, given its recorded activity
+    pub(crate) fn learn(&mut self, activity: &[Activity], rule: &HebbianRule, modulation: f32) {
+        for (layer, activity) in self.layers.iter_mut().zip(activity) {
+            layer.learn(&activity.presynaptic, &activity.outputs, rule, modulation);
+        }
+    }
+
+    /// Adds the gradient of the `loss` on a single sample to `gradient`, one
+    /// value per weight and bias ordered like `weights`, returning the loss.
+    /// Only dense layers can be backpropagated through.
+    pub(crate) fn backpropagate(
+        &self,
+        inputs: &[f32],
+        targets: &[f32],
+        loss: Loss,
+        gradient: &mut [f32],
+    ) -> f32 {
+        assert!(
+            self.layers
+                .iter()
+                .all(|layer| layer.kind == LayerKind::Dense),
+            "Backpropagation only supports dense layers"
+        );
+
+        // Inputs, pre-activations and outputs of every layer
+        let mut records = Vec::with_capacity(self.layers.len());
+        let mut inputs = inputs.to_vec();
+
+        for layer in &self.layers {
+            let preactivations = layer.preactivations(&inputs);
+            let outputs = layer.activation.apply(preactivations.clone());
+
+            records.push((inputs, preactivations, outputs.clone()));
+            inputs = outputs;
+        }
+
+        let value = loss.evaluate(&inputs, targets);
+        let mut gradients = loss.gradient(&inputs, targets);
+        let mut end = gradient.len();
+
+        for (layer, (inputs, preactivations, outputs)) in self.layers.iter().zip(&records).rev() {
+            let start = end - layer.neurons.len() * (inputs.len() + 1);
+
+            gradients = layer.backpropagate(
+                inputs,
+                preactivations,
+                outputs,
+                &gradients,
+                &mut gradient[start..end],
+            );
+            end = start;
+        }
+
+        value
+    }
+
+    /// Clears the memory of every recurrent layer
+    pub fn reset(&mut self) {
+        self.layers.iter_mut().for_each(Layer::reset);
+    }
+
+    /// Layers the network was built from, starting with its inputs
+    pub fn topology(&self) -> Vec<LayerTopology> {
+        use std::iter::once;
+
+        let first = &self.layers[0];
+        let inputs = LayerTopology {
+            // Activation of the input layer is unused
+            neurons: first.neurons[0].weights.len() - first.state.len(),
+            activation: Activation::None,
+            kind: LayerKind::Dense,
+            initializer: Initializer::default(),
+        };
+
+        once(inputs)
+            .chain(self.layers.iter().map(|layer| LayerTopology {
+                neurons: layer.neurons.len() / layer.kind.gates(),
+                activation: layer.activation,
+                kind: layer.kind,
+                // Only matters to `Network::random`, so it isn't remembered
+                initializer: Initializer::default(),
+            }))
+            .collect()
+    }
+
+    /// Number of weights and biases in a network with the given topology
+    pub fn parameters(layers: &[LayerTopology]) -> usize {
+        layers
+            .windows(2)
+            .map(|layers| layers[1].parameters(layers[0].neurons))
+            .sum()
+    }
+
     pub fn weights(&self) -> impl Iterator<Item = f32> + '_ {
         use std::iter::once;
 
@@ -40,6 +170,36 @@ impl Network {
             .cloned()
     }
 
+    /// Zeroes every weight whose magnitude is below `threshold`, returning
+    /// how many were pruned. Biases are left alone.
+    pub fn prune(&mut self, threshold: f32) -> usize {
+        let mut pruned = 0;
+
+        for weight in self
+            .layers
+            .iter_mut()
+            .flat_map(|layer| layer.neurons.iter_mut())
+            .flat_map(|neuron| neuron.weights.iter_mut())
+        {
+            if *weight != 0.0 && weight.abs() < threshold {
+                *weight = 0.0;
+                pruned += 1;
+            }
+        }
+
+        pruned
+    }
+
+    /// Weights and biases, in the same order as `weights`
+    pub(crate) fn weights_mut(&mut self) -> impl Iterator<Item = &mut f32> + '_ {
+        use std::iter::once;
+
+        self.layers
+            .iter_mut()
+            .flat_map(|layer| layer.neurons.iter_mut())
+            .flat_map(|neuron| once(&mut neuron.bias).chain(&mut neuron.weights))
+    }
+
     pub fn from_weights(
         layers: &[LayerTopology],
         weights: impl IntoIterator<Item = f32>,
@@ -55,6 +215,7 @@ impl Network {
                     layers[1].neurons,
                     &mut weights,
                     layers[1].activation,
+                    layers[1].kind,
                 )
             })
             .collect();
@@ -67,6 +228,20 @@ impl Network {
     }
 }
 
+impl Propagate for Network {
+    fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32> {
+        Network::propagate(self, inputs)
+    }
+
+    fn trace(&mut self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        Network::trace(self, inputs)
+    }
+
+    fn reset(&mut self) {
+        Network::reset(self)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -86,10 +261,14 @@ mod tests {
                     LayerTopology {
                         neurons: 2,
                         activation: Activation::None,
+                        kind: LayerKind::Dense,
+                        initializer: Initializer::Uniform,
                     },
                     LayerTopology {
                         neurons: 2,
                         activation: Activation::None,
+                        kind: LayerKind::Dense,
+                        initializer: Initializer::Uniform,
                     },
                 ],
             );
@@ -109,6 +288,50 @@ mod tests {
                 [0.26284897, 0.5238807].as_ref()
             );
         }
+
+        #[test]
+        fn test_initializer() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let network = Network::random(
+                &mut rng,
+                &[
+                    LayerTopology {
+                        neurons: 200,
+                        activation: Activation::None,
+                        kind: LayerKind::Dense,
+                        initializer: Initializer::Uniform,
+                    },
+                    LayerTopology {
+                        neurons: 10,
+                        activation: Activation::ReLU,
+                        kind: LayerKind::Dense,
+                        initializer: Initializer::Xavier,
+                    },
+                ],
+            );
+
+            // Wide layers get proportionally smaller weights
+            let limit = (6.0f32 / 210.0).sqrt();
+            for neuron in &network.layers[0].neurons {
+                assert_eq!(neuron.bias, 0.0);
+                assert!(neuron.weights.iter().all(|weight| weight.abs() <= limit));
+            }
+        }
+
+        #[test]
+        fn test_prelu() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let layer = LayerTopology {
+                neurons: 2,
+                activation: Activation::PReLU(0.25),
+                kind: LayerKind::Dense,
+                initializer: Initializer::Uniform,
+            };
+
+            let network = Network::random(&mut rng, &[layer, layer]);
+
+            assert_eq!(network.layers[0].activation, Activation::PReLU(0.25));
+        }
     }
 
     mod propagate {
@@ -116,7 +339,7 @@ mod tests {
 
         #[test]
         fn test() {
-            let network = Network {
+            let mut network = Network {
                 layers: vec![
                     Layer {
                         neurons: vec![
@@ -130,6 +353,8 @@ mod tests {
                             },
                         ],
                         activation: Activation::None,
+                        kind: LayerKind::Dense,
+                        state: Vec::new(),
                     },
                     Layer {
                         neurons: vec![
@@ -143,6 +368,8 @@ mod tests {
                             },
                         ],
                         activation: Activation::None,
+                        kind: LayerKind::Dense,
+                        state: Vec::new(),
                     },
                 ],
             };
@@ -153,7 +380,7 @@ mod tests {
 
         #[test]
         fn test_activation_functions() {
-            let network = Network {
+            let mut network = Network {
                 layers: vec![
                     Layer {
                         neurons: vec![
@@ -167,6 +394,8 @@ mod tests {
                             },
                         ],
                         activation: Activation::ReLU,
+                        kind: LayerKind::Dense,
+                        state: Vec::new(),
                     },
                     Layer {
                         neurons: vec![
@@ -180,6 +409,8 @@ mod tests {
                             },
                         ],
                         activation: Activation::Softmax,
+                        kind: LayerKind::Dense,
+                        state: Vec::new(),
                     },
                 ],
             };
@@ -189,6 +420,23 @@ mod tests {
             assert_relative_eq!(prop.as_slice(), [0.4028149, 0.5971851].as_ref());
         }
 
+        #[test]
+        fn test_prelu() {
+            let layer = |activation| LayerTopology {
+                neurons: 1,
+                activation,
+                kind: LayerKind::Dense,
+                initializer: Initializer::Uniform,
+            };
+            let mut network = Network::from_weights(
+                &[layer(Activation::None), layer(Activation::PReLU(0.25))],
+                [0.0, 1.0],
+            );
+
+            assert_relative_eq!(network.propagate(vec![2.0])[0], 2.0);
+            assert_relative_eq!(network.propagate(vec![-2.0])[0], -0.5);
+        }
+
         #[test]
         fn test_random_with_activation_functions() {
             let mut rng = ChaCha8Rng::from_seed(Default::default());
@@ -196,26 +444,75 @@ mod tests {
                 LayerTopology {
                     neurons: 6,
                     activation: Activation::ReLU,
+                    kind: LayerKind::Dense,
+                    initializer: Initializer::Uniform,
                 },
                 LayerTopology {
                     neurons: 4,
                     activation: Activation::ReLU,
+                    kind: LayerKind::Dense,
+                    initializer: Initializer::Uniform,
                 },
                 LayerTopology {
                     neurons: 4,
                     activation: Activation::ReLU,
+                    kind: LayerKind::Dense,
+                    initializer: Initializer::Uniform,
                 },
                 LayerTopology {
                     neurons: 4,
                     activation: Activation::Softmax,
+                    kind: LayerKind::Dense,
+                    initializer: Initializer::Uniform,
                 },
             ];
-            let network = Network::random(&mut rng, &topology);
+            let mut network = Network::random(&mut rng, &topology);
             let prop = network.propagate(vec![0.5, 1.0, 0.0, -0.5]);
             assert_relative_eq!(prop.iter().sum::<f32>(), 1.0);
         }
     }
 
+    mod trace {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let topology = [
+                LayerTopology {
+                    neurons: 3,
+                    activation: Activation::None,
+                    kind: LayerKind::Dense,
+                    initializer: Initializer::Uniform,
+                },
+                LayerTopology {
+                    neurons: 4,
+                    activation: Activation::ReLU,
+                    kind: LayerKind::Elman,
+                    initializer: Initializer::Uniform,
+                },
+                LayerTopology {
+                    neurons: 2,
+                    activation: Activation::Tanh,
+                    kind: LayerKind::Dense,
+                    initializer: Initializer::Uniform,
+                },
+            ];
+            let mut network = Network::random(&mut rng, &topology);
+            let mut expected = network.clone();
+
+            for _ in 0..2 {
+                let trace = network.trace(vec![0.5, -1.0, 0.25]);
+                let lengths: Vec<_> = trace.iter().map(Vec::len).collect();
+
+                assert_eq!(lengths, vec![3, 4, 2]);
+                assert_eq!(trace[0], vec![0.5, -1.0, 0.25]);
+                assert!(trace[1].iter().all(|value| *value >= 0.0));
+                assert_eq!(trace[2], expected.propagate(vec![0.5, -1.0, 0.25]));
+            }
+        }
+    }
+
     mod from_weights {
         use super::*;
         use approx::assert_relative_eq;
@@ -226,10 +523,14 @@ mod tests {
                 LayerTopology {
                     neurons: 3,
                     activation: Activation::None,
+                    kind: LayerKind::Dense,
+                    initializer: Initializer::Uniform,
                 },
                 LayerTopology {
                     neurons: 2,
                     activation: Activation::None,
+                    kind: LayerKind::Dense,
+                    initializer: Initializer::Uniform,
                 },
             ];
             let weights = vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8];
@@ -238,5 +539,105 @@ mod tests {
 
             assert_relative_eq!(actual.as_slice(), weights.as_slice());
         }
+
+        #[test]
+        fn test_recurrent() {
+            let layers = &[
+                LayerTopology {
+                    neurons: 3,
+                    activation: Activation::None,
+                    kind: LayerKind::Dense,
+                    initializer: Initializer::Uniform,
+                },
+                LayerTopology {
+                    neurons: 2,
+                    activation: Activation::Tanh,
+                    kind: LayerKind::Gru,
+                    initializer: Initializer::Uniform,
+                },
+                LayerTopology {
+                    neurons: 2,
+                    activation: Activation::Tanh,
+                    kind: LayerKind::Elman,
+                    initializer: Initializer::Uniform,
+                },
+            ];
+            let parameters = Network::parameters(layers);
+            assert_eq!(parameters, 3 * 2 * (3 + 2 + 1) + 2 * (2 + 2 + 1));
+
+            let weights: Vec<_> = (0..parameters).map(|n| n as f32 / 100.0).collect();
+            let network = Network::from_weights(layers, weights.clone());
+            let actual: Vec<_> = network.weights().collect();
+
+            assert_relative_eq!(actual.as_slice(), weights.as_slice());
+        }
+    }
+
+    mod prune {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let layers = &[
+                LayerTopology {
+                    neurons: 2,
+                    activation: Activation::None,
+                    kind: LayerKind::Dense,
+                    initializer: Initializer::Uniform,
+                },
+                LayerTopology {
+                    neurons: 2,
+                    activation: Activation::None,
+                    kind: LayerKind::Dense,
+                    initializer: Initializer::Uniform,
+                },
+            ];
+            let mut network =
+                Network::from_weights(layers, vec![0.01, 0.5, -0.05, -0.02, 0.0, 0.2]);
+
+            assert_eq!(network.prune(0.1), 1);
+            let weights: Vec<_> = network.weights().collect();
+            assert_eq!(weights, vec![0.01, 0.5, 0.0, -0.02, 0.0, 0.2]);
+
+            // Already pruned weights aren't counted again
+            assert_eq!(network.prune(0.1), 0);
+        }
+    }
+
+    mod reset {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let topology = [
+                LayerTopology {
+                    neurons: 2,
+                    activation: Activation::None,
+                    kind: LayerKind::Dense,
+                    initializer: Initializer::Uniform,
+                },
+                LayerTopology {
+                    neurons: 3,
+                    activation: Activation::Tanh,
+                    kind: LayerKind::Elman,
+                    initializer: Initializer::Uniform,
+                },
+                LayerTopology {
+                    neurons: 1,
+                    activation: Activation::None,
+                    kind: LayerKind::Dense,
+                    initializer: Initializer::Uniform,
+                },
+            ];
+            let mut network = Network::random(&mut rng, &topology);
+
+            let first = network.propagate(vec![0.5, -0.5]);
+            let second = network.propagate(vec![0.5, -0.5]);
+            assert_ne!(first, second);
+
+            network.reset();
+            assert_eq!(network.propagate(vec![0.5, -0.5]), first);
+        }
     }
 }
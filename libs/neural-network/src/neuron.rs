@@ -7,23 +7,23 @@ pub(crate) struct Neuron {
 }
 
 impl Neuron {
-    pub fn random(rng: &mut dyn rand::RngCore, output_size: usize) -> Neuron {
+    pub fn random(
+        rng: &mut dyn rand::RngCore,
+        output_size: usize,
+        fan_out: usize,
+        initializer: Initializer,
+    ) -> Neuron {
         let weights = (0..output_size)
-            .map(|_| rng.gen_range(-1.0..=1.0))
+            .map(|_| initializer.weight(rng, output_size, fan_out))
             .collect();
 
-        let bias = rng.gen_range(-1.0..=1.0);
+        let bias = initializer.bias(rng);
 
         Neuron { weights, bias }
     }
 
     pub(crate) fn propagate(&self, inputs: &[f32]) -> f32 {
-        inputs
-            .iter()
-            .zip(&self.weights)
-            .map(|(input, weight)| weight * input)
-            .sum::<f32>()
-            + self.bias
+        simd::dot(&self.weights, inputs) + self.bias
     }
 
     pub fn from_weights(output_neurons: usize, weights: &mut dyn Iterator<Item = f32>) -> Neuron {
@@ -49,7 +49,7 @@ mod tests {
         #[test]
         fn test() {
             let mut rng = ChaCha8Rng::from_seed(Default::default());
-            let neuron = Neuron::random(&mut rng, 2);
+            let neuron = Neuron::random(&mut rng, 2, 1, Initializer::Uniform);
 
             assert_relative_eq!(neuron.bias, 0.8181262);
             assert_relative_eq!(neuron.weights.as_slice(), [-0.6255188, 0.67383957].as_ref());
@@ -0,0 +1,196 @@
+use crate::*;
+
+/// Network whose weights keep adapting after it's built, by applying a
+/// Hebbian rule to the activity of its latest propagation.
+///
+/// The innate weights are kept apart from the learned ones, so that
+/// `weights` describes the network as it was built, not what it has learned.
+#[derive(Debug, Clone)]
+pub struct PlasticNetwork {
+    innate: Network,
+    network: Network,
+    rule: HebbianRule,
+    activity: Vec<Activity>,
+}
+
+/// What the neurons of a layer received and emitted during a propagation
+#[derive(Debug, Clone)]
+pub(crate) struct Activity {
+    pub(crate) presynaptic: Vec<f32>,
+    pub(crate) outputs: Vec<f32>,
+}
+
+impl PlasticNetwork {
+    pub fn new(network: Network, rule: HebbianRule) -> PlasticNetwork {
+        PlasticNetwork {
+            innate: network.clone(),
+            network,
+            rule,
+            activity: Vec::new(),
+        }
+    }
+
+    pub fn random(rng: &mut dyn rand::RngCore, layers: &[LayerTopology]) -> PlasticNetwork {
+        let network = Network::random(rng, layers);
+        let rule = HebbianRule::random(rng);
+
+        PlasticNetwork::new(network, rule)
+    }
+
+    /// Number of weights, biases and rule coefficients of a network with the given topology
+    pub fn parameters(layers: &[LayerTopology]) -> usize {
+        Network::parameters(layers) + HebbianRule::PARAMETERS
+    }
+
+    /// Innate weights and biases, followed by the coefficients of the rule
+    pub fn weights(&self) -> impl Iterator<Item = f32> + '_ {
+        self.innate.weights().chain(self.rule.weights())
+    }
+
+    pub fn from_weights(
+        layers: &[LayerTopology],
+        weights: impl IntoIterator<Item = f32>,
+    ) -> PlasticNetwork {
+        let weights: Vec<_> = weights.into_iter().collect();
+        let (network, rule) = weights.split_at(
+            weights
+                .len()
+                .checked_sub(HebbianRule::PARAMETERS)
+                .expect("Not enough weights!"),
+        );
+
+        PlasticNetwork::new(
+            Network::from_weights(layers, network.iter().copied()),
+            HebbianRule::from_weights(&mut rule.iter().copied()),
+        )
+    }
+
+    pub fn rule(&self) -> &HebbianRule {
+        &self.rule
+    }
+
+    /// Feeds the inputs through the network, remembering the activity the
+    /// next call to `learn` adapts to
+    pub fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32> {
+        let (outputs, activity) = self.network.propagate_recorded(inputs);
+        self.activity = activity;
+
+        outputs
+    }
+
+    /// Propagates like `propagate`, returning the inputs followed by the
+    /// outputs of every layer
+    pub fn trace(&mut self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        let (_, activity) = self.network.propagate_recorded(inputs.clone());
+        let trace = std::iter::once(inputs)
+            .chain(activity.iter().map(|activity| activity.outputs.clone()))
+            .collect();
+        self.activity = activity;
+
+        trace
+    }
+
+    /// Adapts the weights to the latest propagation, scaled by `modulation`
+    pub fn learn(&mut self, modulation: f32) {
+        self.network.learn(&self.activity, &self.rule, modulation);
+    }
+
+    /// Forgets everything learned, returning to the innate weights
+    pub fn reset(&mut self) {
+        self.network = self.innate.clone();
+        self.activity.clear();
+    }
+}
+
+impl Propagate for PlasticNetwork {
+    fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32> {
+        PlasticNetwork::propagate(self, inputs)
+    }
+
+    fn trace(&mut self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        PlasticNetwork::trace(self, inputs)
+    }
+
+    fn reset(&mut self) {
+        PlasticNetwork::reset(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn layers() -> Vec<LayerTopology> {
+        vec![
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::None,
+                kind: LayerKind::Dense,
+                initializer: Initializer::Uniform,
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::None,
+                kind: LayerKind::Dense,
+                initializer: Initializer::Uniform,
+            },
+        ]
+    }
+
+    fn network() -> PlasticNetwork {
+        // A single connection of weight 0.5, learning through pure correlation
+        PlasticNetwork::from_weights(&layers(), vec![0.0, 0.5, 1.0, 1.0, 0.0, 0.0, 0.0])
+    }
+
+    #[test]
+    fn test_learn() {
+        let mut network = network();
+
+        assert_relative_eq!(network.propagate(vec![1.0])[0], 0.5);
+
+        // Δw = 1.0 * 1.0 * 1.0 * 0.5
+        network.learn(1.0);
+        assert_relative_eq!(network.propagate(vec![1.0])[0], 1.0);
+
+        // Unmodulated activity isn't learned
+        network.learn(0.0);
+        assert_relative_eq!(network.propagate(vec![1.0])[0], 1.0);
+    }
+
+    #[test]
+    fn test_learning_isnt_inherited() {
+        let mut network = network();
+        network.propagate(vec![1.0]);
+        network.learn(1.0);
+
+        let weights: Vec<_> = network.weights().collect();
+        assert_relative_eq!(
+            weights.as_slice(),
+            [0.0, 0.5, 1.0, 1.0, 0.0, 0.0, 0.0].as_ref()
+        );
+
+        network.reset();
+        assert_relative_eq!(network.propagate(vec![1.0])[0], 0.5);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network = PlasticNetwork::random(&mut rng, &layers());
+
+        assert_eq!(
+            network.weights().count(),
+            PlasticNetwork::parameters(&layers())
+        );
+        assert_eq!(PlasticNetwork::parameters(&layers()), 2 + 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough weights!")]
+    fn test_not_enough_weights() {
+        PlasticNetwork::from_weights(&layers(), vec![0.0; 3]);
+    }
+}
@@ -0,0 +1,14 @@
+/// Maps inputs to outputs, possibly remembering state between calls
+pub trait Propagate {
+    fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32>;
+
+    /// Propagates like `propagate`, returning the inputs, then the
+    /// activations of each hidden layer and finally the outputs
+    fn trace(&mut self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        let outputs = self.propagate(inputs.clone());
+        vec![inputs, outputs]
+    }
+
+    /// Clears any state remembered between calls
+    fn reset(&mut self) {}
+}
@@ -0,0 +1,279 @@
+use crate::*;
+
+/// Largest magnitude of a quantized value
+const LEVELS: f32 = i8::MAX as f32;
+
+/// Network running on int8 weights and inputs, accumulated in i32, whose
+/// weights take a quarter of the memory of a float network's. Each
+/// neuron's weights share a scale, and each layer's inputs share a scale
+/// calibrated on recorded inputs; inputs beyond the calibrated range
+/// saturate. Biases and activations stay in floating point. Dense and Elman
+/// layers are supported.
+#[derive(Debug, Clone)]
+pub struct QuantizedNetwork {
+    layers: Vec<QuantizedLayer>,
+}
+
+#[derive(Debug, Clone)]
+struct QuantizedLayer {
+    activation: Activation,
+    kind: LayerKind,
+    input_scale: f32,
+    weights: Vec<i8>, // Row-major, one row per neuron
+    weight_scales: Vec<f32>,
+    biases: Vec<f32>,
+    state: Vec<f32>,
+}
+
+impl QuantizedNetwork {
+    /// Quantizes the network, calibrating the range of each layer's inputs
+    /// by propagating the recorded `samples` in order from a reset state
+    pub fn calibrate(network: &Network, samples: &[Vec<f32>]) -> QuantizedNetwork {
+        let mut ranges = vec![0.0f32; network.layers.len()];
+        let mut recorder = network.clone();
+        recorder.reset();
+
+        for sample in samples {
+            let trace = recorder.trace(sample.clone());
+
+            for (layer, range) in ranges.iter_mut().enumerate() {
+                // Recurrent layers also see their own outputs
+                let inputs = if network.layers[layer].kind.is_recurrent() {
+                    &trace[layer..=layer + 1]
+                } else {
+                    &trace[layer..=layer]
+                };
+
+                *range = inputs
+                    .iter()
+                    .flatten()
+                    .fold(*range, |range, x| range.max(x.abs()));
+            }
+        }
+
+        let layers = network
+            .layers
+            .iter()
+            .zip(ranges)
+            .map(|(layer, range)| QuantizedLayer::new(layer, range))
+            .collect();
+
+        QuantizedNetwork { layers }
+    }
+
+    pub fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32> {
+        self.layers
+            .iter_mut()
+            .fold(inputs, |inputs, layer| layer.propagate(inputs))
+    }
+
+    /// Propagates like `propagate`, returning the inputs followed by the
+    /// outputs of every layer, the last of which are the network's outputs
+    pub fn trace(&mut self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        let mut trace = Vec::with_capacity(self.layers.len() + 1);
+        trace.push(inputs);
+
+        for layer in &mut self.layers {
+            let outputs = layer.propagate(trace[trace.len() - 1].clone());
+            trace.push(outputs);
+        }
+
+        trace
+    }
+
+    /// Clears the memory of every recurrent layer
+    pub fn reset(&mut self) {
+        for layer in &mut self.layers {
+            layer.state.iter_mut().for_each(|h| *h = 0.0);
+        }
+    }
+}
+
+impl Propagate for QuantizedNetwork {
+    fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32> {
+        QuantizedNetwork::propagate(self, inputs)
+    }
+
+    fn trace(&mut self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        QuantizedNetwork::trace(self, inputs)
+    }
+
+    fn reset(&mut self) {
+        QuantizedNetwork::reset(self)
+    }
+}
+
+impl QuantizedLayer {
+    fn new(layer: &Layer, range: f32) -> QuantizedLayer {
+        assert!(
+            layer.kind != LayerKind::Gru,
+            "Quantized networks support dense and Elman layers, not {}",
+            layer.kind
+        );
+
+        let mut weights = Vec::new();
+        let mut weight_scales = Vec::new();
+
+        for neuron in &layer.neurons {
+            let scale = scale(
+                neuron
+                    .weights
+                    .iter()
+                    .fold(0.0f32, |max, w| max.max(w.abs())),
+            );
+
+            weights.extend(neuron.weights.iter().map(|w| quantize(*w, scale)));
+            weight_scales.push(scale);
+        }
+
+        QuantizedLayer {
+            activation: layer.activation,
+            kind: layer.kind,
+            input_scale: scale(range),
+            weights,
+            weight_scales,
+            biases: layer.neurons.iter().map(|neuron| neuron.bias).collect(),
+            state: layer.state.clone(),
+        }
+    }
+
+    fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32> {
+        let inputs: Vec<_> = inputs
+            .iter()
+            .chain(&self.state)
+            .map(|x| quantize(*x, self.input_scale))
+            .collect();
+
+        let outputs = self
+            .weights
+            .chunks_exact(inputs.len())
+            .zip(&self.weight_scales)
+            .zip(&self.biases)
+            .map(|((row, weight_scale), bias)| {
+                // Products of two int8 values always fit in i16
+                let sum: i32 = row
+                    .iter()
+                    .zip(&inputs)
+                    .map(|(w, x)| (*w as i16 * *x as i16) as i32)
+                    .sum();
+
+                sum as f32 * weight_scale * self.input_scale + bias
+            })
+            .collect();
+        let outputs = self.activation.apply(outputs);
+
+        if self.kind == LayerKind::Elman {
+            self.state.copy_from_slice(&outputs);
+        }
+
+        outputs
+    }
+}
+
+/// Step between quantized values spanning -range..=range
+fn scale(range: f32) -> f32 {
+    if range > 0.0 {
+        range / LEVELS
+    } else {
+        1.0
+    }
+}
+
+fn quantize(value: f32, scale: f32) -> i8 {
+    (value / scale).round().clamp(-LEVELS, LEVELS) as i8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    fn topology(kind: LayerKind) -> Vec<LayerTopology> {
+        vec![
+            LayerTopology {
+                neurons: 8,
+                activation: Activation::None,
+                kind: LayerKind::Dense,
+                initializer: Initializer::Uniform,
+            },
+            LayerTopology {
+                neurons: 16,
+                activation: Activation::Tanh,
+                kind,
+                // Keeps the Elman layer from amplifying rounding errors chaotically
+                initializer: Initializer::Xavier,
+            },
+            LayerTopology {
+                neurons: 4,
+                activation: Activation::Tanh,
+                kind: LayerKind::Dense,
+                initializer: Initializer::Uniform,
+            },
+        ]
+    }
+
+    fn samples(rng: &mut ChaCha8Rng) -> Vec<Vec<f32>> {
+        (0..100)
+            .map(|_| (0..8).map(|_| rng.gen_range(-1.0..=1.0)).collect())
+            .collect()
+    }
+
+    fn assert_close_to_network(kind: LayerKind) {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network = Network::random(&mut rng, &topology(kind));
+        let samples = samples(&mut rng);
+        let mut quantized = QuantizedNetwork::calibrate(&network, &samples);
+
+        let report = DriftReport::measure(&mut network, &mut quantized, &samples);
+        assert!(report.max_error < 0.05, "{:?}", report);
+        assert!(report.agreement > 0.9, "{:?}", report);
+    }
+
+    #[test]
+    fn test_dense() {
+        assert_close_to_network(LayerKind::Dense);
+    }
+
+    #[test]
+    fn test_elman() {
+        assert_close_to_network(LayerKind::Elman);
+    }
+
+    #[test]
+    fn test_saturation() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network = Network::random(&mut rng, &topology(LayerKind::Dense));
+        let mut quantized = QuantizedNetwork::calibrate(&network, &[vec![0.5; 8]]);
+
+        // Inputs past the calibrated range behave like the range's edge
+        assert_eq!(
+            quantized.propagate(vec![10.0; 8]),
+            quantized.propagate(vec![0.5; 8])
+        );
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network = Network::random(&mut rng, &topology(LayerKind::Elman));
+        let samples = samples(&mut rng);
+        let mut quantized = QuantizedNetwork::calibrate(&network, &samples);
+        let mut expected = quantized.clone();
+
+        let trace = quantized.trace(samples[0].clone());
+        let lengths: Vec<_> = trace.iter().map(Vec::len).collect();
+
+        assert_eq!(lengths, [8, 16, 4]);
+        assert_eq!(trace[2], expected.propagate(samples[0].clone()));
+    }
+
+    #[test]
+    #[should_panic(expected = "not gru")]
+    fn test_gru() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network = Network::random(&mut rng, &topology(LayerKind::Gru));
+
+        let _ = QuantizedNetwork::calibrate(&network, &[]);
+    }
+}
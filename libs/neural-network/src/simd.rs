@@ -0,0 +1,154 @@
+//! Kernels behind the matrix path and the activations. With the `simd`
+//! feature they process eight lanes at a time using `wide`, which lowers to
+//! SSE/AVX on x86 and to simd128 on wasm when built with
+//! `-C target-feature=+simd128`; otherwise the scalar kernels are used.
+
+#[cfg(feature = "simd")]
+pub(crate) use self::lanes::*;
+#[cfg(not(feature = "simd"))]
+pub(crate) use self::scalar::*;
+
+/// `exp` of the lanes saturates to zero beyond ±87.3, so arguments are kept within
+#[cfg(feature = "simd")]
+const EXP_LIMIT: f32 = 80.0;
+
+// With the `simd` feature, the scalar kernels serve as a reference in tests
+#[cfg_attr(feature = "simd", allow(dead_code))]
+pub(crate) mod scalar {
+    use std::f32::consts::E;
+
+    pub(crate) fn dot(a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b).map(|(a, b)| a * b).sum()
+    }
+
+    pub(crate) fn relu(values: &mut [f32]) {
+        values.iter_mut().for_each(|x| *x = x.max(0.0));
+    }
+
+    pub(crate) fn sigmoid(values: &mut [f32]) {
+        values
+            .iter_mut()
+            .for_each(|x| *x = 1.0 / (1.0 + E.powf(-*x)));
+    }
+
+    pub(crate) fn tanh(values: &mut [f32]) {
+        values.iter_mut().for_each(|x| *x = x.tanh());
+    }
+}
+
+#[cfg(feature = "simd")]
+mod lanes {
+    use super::EXP_LIMIT;
+    use wide::f32x8;
+
+    const LANES: usize = 8;
+
+    pub(crate) fn dot(a: &[f32], b: &[f32]) -> f32 {
+        let len = a.len().min(b.len());
+        let (a, b) = (&a[..len], &b[..len]);
+
+        let mut sum = f32x8::ZERO;
+        let mut a_chunks = a.chunks_exact(LANES);
+        let mut b_chunks = b.chunks_exact(LANES);
+
+        for (a, b) in (&mut a_chunks).zip(&mut b_chunks) {
+            sum = load(a).mul_add(load(b), sum);
+        }
+
+        sum.reduce_add() + super::scalar::dot(a_chunks.remainder(), b_chunks.remainder())
+    }
+
+    pub(crate) fn relu(values: &mut [f32]) {
+        map(values, |x| x.max(f32x8::ZERO));
+    }
+
+    pub(crate) fn sigmoid(values: &mut [f32]) {
+        map(values, sigmoid_lanes);
+    }
+
+    /// tanh(x) = 2·sigmoid(2x) - 1
+    pub(crate) fn tanh(values: &mut [f32]) {
+        map(values, |x| {
+            sigmoid_lanes(x + x).mul_add(f32x8::splat(2.0), f32x8::splat(-1.0))
+        });
+    }
+
+    fn sigmoid_lanes(x: f32x8) -> f32x8 {
+        let x = x
+            .fast_max(f32x8::splat(-EXP_LIMIT))
+            .fast_min(f32x8::splat(EXP_LIMIT));
+        f32x8::ONE / (f32x8::ONE + (-x).exp())
+    }
+
+    /// Applies `f` eight values at a time, padding the remainder with zeros
+    fn map(values: &mut [f32], f: impl Fn(f32x8) -> f32x8) {
+        for chunk in values.chunks_mut(LANES) {
+            let len = chunk.len();
+            chunk.copy_from_slice(&f(load(chunk)).to_array()[..len]);
+        }
+    }
+
+    /// Loads up to eight values, padding the missing lanes with zeros
+    fn load(values: &[f32]) -> f32x8 {
+        let mut lanes = [0.0; LANES];
+        lanes[..values.len()].copy_from_slice(values);
+        f32x8::from(lanes)
+    }
+}
+
+#[cfg(all(test, feature = "simd"))]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    /// Long enough to cover both full lanes and a remainder
+    fn values() -> Vec<f32> {
+        (0..203).map(|n| (n as f32 - 101.0) / 10.0).collect()
+    }
+
+    fn assert_matches_scalar(f: fn(&mut [f32]), scalar: fn(&mut [f32]), epsilon: f32) {
+        let mut actual = values();
+        let mut expected = values();
+
+        f(&mut actual);
+        scalar(&mut expected);
+
+        assert_abs_diff_eq!(actual.as_slice(), expected.as_slice(), epsilon = epsilon);
+    }
+
+    #[test]
+    fn test_dot() {
+        let a = values();
+        let b: Vec<_> = a.iter().map(|x| x.sin()).collect();
+
+        assert_abs_diff_eq!(dot(&a, &b), scalar::dot(&a, &b), epsilon = 1e-3);
+        assert_abs_diff_eq!(dot(&a[..5], &b[..5]), scalar::dot(&a[..5], &b[..5]));
+    }
+
+    #[test]
+    fn test_relu() {
+        assert_matches_scalar(relu, scalar::relu, 0.0);
+    }
+
+    #[test]
+    fn test_sigmoid() {
+        assert_matches_scalar(sigmoid, scalar::sigmoid, 1e-6);
+    }
+
+    #[test]
+    fn test_tanh() {
+        assert_matches_scalar(tanh, scalar::tanh, 1e-6);
+    }
+
+    #[test]
+    fn test_saturation() {
+        let mut values = vec![-1000.0, -100.0, 100.0, 1000.0, 0.0, 0.0, 0.0, 0.0];
+
+        sigmoid(&mut values);
+        assert_abs_diff_eq!(
+            values.as_slice(),
+            [0.0, 0.0, 1.0, 1.0, 0.5, 0.5, 0.5, 0.5].as_ref(),
+            epsilon = 1e-6
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use crate::*;
+
+/// Network keeping only its non-zero weights, stored row by row (CSR), so
+/// that a pruned network propagates in proportion to its remaining
+/// connections. Dense and Elman layers are supported.
+#[derive(Debug, Clone)]
+pub struct SparseNetwork {
+    layers: Vec<SparseLayer>,
+}
+
+#[derive(Debug, Clone)]
+struct SparseLayer {
+    activation: Activation,
+    kind: LayerKind,
+    biases: Vec<f32>,
+    rows: Vec<usize>,    // Start of each neuron's connections, followed by their end
+    columns: Vec<usize>, // Input feeding each connection, past the inputs for recurrent ones
+    weights: Vec<f32>,
+    state: Vec<f32>,
+}
+
+impl SparseNetwork {
+    /// Number of connections left, out of every weight of the dense network
+    pub fn connections(&self) -> usize {
+        self.layers.iter().map(|layer| layer.weights.len()).sum()
+    }
+
+    pub fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32> {
+        self.layers
+            .iter_mut()
+            .fold(inputs, |inputs, layer| layer.propagate(inputs))
+    }
+
+    /// Propagates like `propagate`, returning the inputs followed by the
+    /// outputs of every layer, the last of which are the network's outputs
+    pub fn trace(&mut self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        let mut trace = Vec::with_capacity(self.layers.len() + 1);
+        trace.push(inputs);
+
+        for layer in &mut self.layers {
+            let outputs = layer.propagate(trace[trace.len() - 1].clone());
+            trace.push(outputs);
+        }
+
+        trace
+    }
+
+    /// Clears the memory of every recurrent layer
+    pub fn reset(&mut self) {
+        for layer in &mut self.layers {
+            layer.state.iter_mut().for_each(|h| *h = 0.0);
+        }
+    }
+}
+
+/// Drops the zero weights of a network, e.g. after `Network::prune`
+impl From<&Network> for SparseNetwork {
+    fn from(network: &Network) -> SparseNetwork {
+        let layers = network
+            .layers
+            .iter()
+            .map(|layer| {
+                assert!(
+                    layer.kind != LayerKind::Gru,
+                    "Sparse networks support dense and Elman layers, not {}",
+                    layer.kind
+                );
+
+                let mut rows = vec![0];
+                let mut columns = Vec::new();
+                let mut weights = Vec::new();
+
+                for neuron in &layer.neurons {
+                    for (column, weight) in neuron.weights.iter().enumerate() {
+                        if *weight != 0.0 {
+                            columns.push(column);
+                            weights.push(*weight);
+                        }
+                    }
+
+                    rows.push(weights.len());
+                }
+
+                SparseLayer {
+                    activation: layer.activation,
+                    kind: layer.kind,
+                    biases: layer.neurons.iter().map(|neuron| neuron.bias).collect(),
+                    rows,
+                    columns,
+                    weights,
+                    state: layer.state.clone(),
+                }
+            })
+            .collect();
+
+        SparseNetwork { layers }
+    }
+}
+
+impl Propagate for SparseNetwork {
+    fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32> {
+        SparseNetwork::propagate(self, inputs)
+    }
+
+    fn trace(&mut self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        SparseNetwork::trace(self, inputs)
+    }
+
+    fn reset(&mut self) {
+        SparseNetwork::reset(self)
+    }
+}
+
+impl SparseLayer {
+    fn propagate(&mut self, mut inputs: Vec<f32>) -> Vec<f32> {
+        inputs.extend_from_slice(&self.state);
+
+        let outputs = self
+            .biases
+            .iter()
+            .zip(self.rows.windows(2))
+            .map(|(bias, row)| {
+                let (start, end) = (row[0], row[1]);
+
+                self.columns[start..end]
+                    .iter()
+                    .zip(&self.weights[start..end])
+                    .map(|(column, weight)| weight * inputs[*column])
+                    .sum::<f32>()
+                    + bias
+            })
+            .collect();
+        let outputs = self.activation.apply(outputs);
+
+        if self.kind == LayerKind::Elman {
+            self.state.copy_from_slice(&outputs);
+        }
+
+        outputs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn topology(kind: LayerKind) -> Vec<LayerTopology> {
+        vec![
+            LayerTopology {
+                neurons: 4,
+                activation: Activation::None,
+                kind: LayerKind::Dense,
+                initializer: Initializer::Uniform,
+            },
+            LayerTopology {
+                neurons: 6,
+                activation: Activation::Tanh,
+                kind,
+                initializer: Initializer::Uniform,
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::None,
+                kind: LayerKind::Dense,
+                initializer: Initializer::Uniform,
+            },
+        ]
+    }
+
+    fn assert_matches_network(kind: LayerKind) {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network = Network::random(&mut rng, &topology(kind));
+        let pruned = network.prune(0.5);
+        let mut sparse = SparseNetwork::from(&network);
+
+        assert_eq!(
+            sparse.connections() + pruned,
+            Network::parameters(&topology(kind)) - 6 - 2
+        );
+
+        for step in 0..5 {
+            let inputs = vec![0.5, -0.25 * step as f32, 1.0, 0.1];
+            let expected = network.propagate(inputs.clone());
+            let actual = sparse.propagate(inputs);
+
+            assert_relative_eq!(actual.as_slice(), expected.as_slice(), epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_dense() {
+        assert_matches_network(LayerKind::Dense);
+    }
+
+    #[test]
+    fn test_elman() {
+        assert_matches_network(LayerKind::Elman);
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network = Network::random(&mut rng, &topology(LayerKind::Elman));
+        network.prune(0.5);
+        let mut sparse = SparseNetwork::from(&network);
+
+        for step in 0..3 {
+            let inputs = vec![0.5, -0.25 * step as f32, 1.0, 0.1];
+            let expected = network.trace(inputs.clone());
+            let actual = sparse.trace(inputs);
+
+            assert_eq!(actual.len(), expected.len());
+            for (actual, expected) in actual.iter().zip(&expected) {
+                assert_relative_eq!(actual.as_slice(), expected.as_slice(), epsilon = 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "not gru")]
+    fn test_gru() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network = Network::random(&mut rng, &topology(LayerKind::Gru));
+
+        let _ = SparseNetwork::from(&network);
+    }
+}
@@ -0,0 +1,389 @@
+use rand::seq::SliceRandom;
+
+use crate::*;
+
+/// Outputs are clamped away from zero before taking their logarithm
+const EPSILON: f32 = 1e-7;
+
+/// Measures how far a network's outputs are from the targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Loss {
+    /// Mean of the squared differences
+    MeanSquaredError,
+    /// Categorical cross-entropy, expecting probabilities such as the
+    /// outputs of a Softmax layer
+    CrossEntropy,
+}
+
+impl Loss {
+    pub fn evaluate(&self, outputs: &[f32], targets: &[f32]) -> f32 {
+        assert_eq!(outputs.len(), targets.len());
+
+        match *self {
+            Loss::MeanSquaredError => {
+                outputs
+                    .iter()
+                    .zip(targets)
+                    .map(|(y, t)| (y - t).powi(2))
+                    .sum::<f32>()
+                    / outputs.len() as f32
+            }
+            Loss::CrossEntropy => -outputs
+                .iter()
+                .zip(targets)
+                .map(|(y, t)| t * y.max(EPSILON).ln())
+                .sum::<f32>(),
+        }
+    }
+
+    /// Gradient of the loss with respect to each output
+    pub(crate) fn gradient(&self, outputs: &[f32], targets: &[f32]) -> Vec<f32> {
+        assert_eq!(outputs.len(), targets.len());
+
+        match *self {
+            Loss::MeanSquaredError => outputs
+                .iter()
+                .zip(targets)
+                .map(|(y, t)| 2.0 * (y - t) / outputs.len() as f32)
+                .collect(),
+            Loss::CrossEntropy => outputs
+                .iter()
+                .zip(targets)
+                .map(|(y, t)| -t / y.max(EPSILON))
+                .collect(),
+        }
+    }
+}
+
+/// How the weights follow the gradient
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Optimizer {
+    /// Stochastic gradient descent, accumulating past steps with momentum
+    Sgd { learning_rate: f32, momentum: f32 },
+    /// Kingma & Ba, scaling each step by running moments of the gradient
+    Adam {
+        learning_rate: f32,
+        beta1: f32,
+        beta2: f32,
+        epsilon: f32,
+    },
+}
+
+impl Optimizer {
+    /// Plain gradient descent, without momentum
+    pub fn sgd(learning_rate: f32) -> Optimizer {
+        Optimizer::Sgd {
+            learning_rate,
+            momentum: 0.0,
+        }
+    }
+
+    /// Adam with its customary decay rates
+    pub fn adam(learning_rate: f32) -> Optimizer {
+        Optimizer::Adam {
+            learning_rate,
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1e-8,
+        }
+    }
+}
+
+/// Running state of an optimizer, one value per weight and bias
+struct OptimizerState {
+    first: Vec<f32>,  // Velocity for SGD, mean of the gradient for Adam
+    second: Vec<f32>, // Uncentered variance of the gradient, for Adam
+    steps: i32,
+}
+
+impl OptimizerState {
+    fn new(parameters: usize) -> OptimizerState {
+        OptimizerState {
+            first: vec![0.0; parameters],
+            second: vec![0.0; parameters],
+            steps: 0,
+        }
+    }
+
+    fn step<'a>(
+        &mut self,
+        optimizer: &Optimizer,
+        weights: impl Iterator<Item = &'a mut f32>,
+        gradient: &[f32],
+    ) {
+        self.steps += 1;
+
+        let state = self.first.iter_mut().zip(self.second.iter_mut());
+
+        for ((weight, g), (first, second)) in weights.zip(gradient).zip(state) {
+            match *optimizer {
+                Optimizer::Sgd {
+                    learning_rate,
+                    momentum,
+                } => {
+                    *first = momentum * *first - learning_rate * g;
+                    *weight += *first;
+                }
+                Optimizer::Adam {
+                    learning_rate,
+                    beta1,
+                    beta2,
+                    epsilon,
+                } => {
+                    *first = beta1 * *first + (1.0 - beta1) * g;
+                    *second = beta2 * *second + (1.0 - beta2) * g * g;
+
+                    let first = *first / (1.0 - beta1.powi(self.steps));
+                    let second = *second / (1.0 - beta2.powi(self.steps));
+
+                    *weight -= learning_rate * first / (second.sqrt() + epsilon);
+                }
+            }
+        }
+    }
+}
+
+/// Inputs along with the outputs a network should learn to produce for them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub inputs: Vec<f32>,
+    pub targets: Vec<f32>,
+}
+
+impl Sample {
+    pub fn new(inputs: Vec<f32>, targets: Vec<f32>) -> Sample {
+        Sample { inputs, targets }
+    }
+}
+
+/// Supervised training of a `Network` through backpropagation, e.g. to
+/// imitate a hand-crafted controller before evolution takes over
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trainer {
+    pub loss: Loss,
+    pub optimizer: Optimizer,
+    pub epochs: usize,     // Passes over the whole set of samples
+    pub batch_size: usize, // Samples whose gradients are averaged into each step
+}
+
+impl Trainer {
+    pub fn new(loss: Loss, optimizer: Optimizer, epochs: usize, batch_size: usize) -> Trainer {
+        Trainer {
+            loss,
+            optimizer,
+            epochs,
+            batch_size,
+        }
+    }
+
+    /// Trains the network on the samples in a shuffled order, returning the
+    /// mean loss of each epoch
+    pub fn train(
+        &self,
+        rng: &mut dyn rand::RngCore,
+        network: &mut Network,
+        samples: &[Sample],
+    ) -> Vec<f32> {
+        assert!(!samples.is_empty(), "There must be samples to train on");
+        assert!(self.batch_size > 0, "Batches must hold at least one sample");
+
+        let parameters = network.weights().count();
+        let mut state = OptimizerState::new(parameters);
+        let mut gradient = vec![0.0; parameters];
+        let mut order: Vec<_> = (0..samples.len()).collect();
+
+        (0..self.epochs)
+            .map(|_| {
+                order.shuffle(rng);
+
+                let mut total = 0.0;
+
+                for batch in order.chunks(self.batch_size) {
+                    gradient.iter_mut().for_each(|g| *g = 0.0);
+
+                    for sample in batch.iter().map(|&n| &samples[n]) {
+                        total += network.backpropagate(
+                            &sample.inputs,
+                            &sample.targets,
+                            self.loss,
+                            &mut gradient,
+                        );
+                    }
+
+                    gradient.iter_mut().for_each(|g| *g /= batch.len() as f32);
+                    state.step(&self.optimizer, network.weights_mut(), &gradient);
+                }
+
+                total / samples.len() as f32
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn topology(hidden: usize, output: Activation) -> Vec<LayerTopology> {
+        vec![
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::None,
+                kind: LayerKind::Dense,
+                initializer: Initializer::Uniform,
+            },
+            LayerTopology {
+                neurons: hidden,
+                activation: Activation::Tanh,
+                kind: LayerKind::Dense,
+                initializer: Initializer::Xavier,
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: output,
+                kind: LayerKind::Dense,
+                initializer: Initializer::Xavier,
+            },
+        ]
+    }
+
+    mod loss {
+        use super::*;
+
+        #[test]
+        fn test_mean_squared_error() {
+            let loss = Loss::MeanSquaredError.evaluate(&[1.0, 0.0], &[0.0, 0.0]);
+            let gradient = Loss::MeanSquaredError.gradient(&[1.0, 0.0], &[0.0, 0.0]);
+
+            assert_relative_eq!(loss, 0.5);
+            assert_relative_eq!(gradient.as_slice(), [1.0, 0.0].as_ref());
+        }
+
+        #[test]
+        fn test_cross_entropy() {
+            let loss = Loss::CrossEntropy.evaluate(&[0.25, 0.75], &[0.0, 1.0]);
+            let certain = Loss::CrossEntropy.evaluate(&[0.0, 1.0], &[0.0, 1.0]);
+
+            assert_relative_eq!(loss, -(0.75f32.ln()));
+            assert_relative_eq!(certain, 0.0);
+        }
+
+        #[test]
+        fn test_cross_entropy_of_zero() {
+            let loss = Loss::CrossEntropy.evaluate(&[1.0, 0.0], &[0.0, 1.0]);
+
+            assert!(loss.is_finite());
+        }
+    }
+
+    mod backpropagate {
+        use super::*;
+
+        #[test]
+        fn test_matches_finite_differences() {
+            for (output, loss, targets) in [
+                (Activation::Sigmoid, Loss::MeanSquaredError, [0.2, 0.9]),
+                (Activation::Softmax, Loss::CrossEntropy, [0.0, 1.0]),
+            ] {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let topology = topology(3, output);
+                let network = Network::random(&mut rng, &topology);
+                let inputs = [0.5, -0.8];
+
+                let mut actual = vec![0.0; Network::parameters(&topology)];
+                network.backpropagate(&inputs, &targets, loss, &mut actual);
+
+                let weights: Vec<_> = network.weights().collect();
+                let h = 1e-2;
+
+                for (n, actual) in actual.iter().enumerate() {
+                    let loss_at = |offset: f32| {
+                        let mut weights = weights.clone();
+                        weights[n] += offset;
+
+                        let mut network = Network::from_weights(&topology, weights);
+                        loss.evaluate(&network.propagate(inputs.to_vec()), &targets)
+                    };
+                    let expected = (loss_at(h) - loss_at(-h)) / (2.0 * h);
+
+                    assert_relative_eq!(*actual, expected, epsilon = 1e-3);
+                }
+            }
+        }
+
+        #[test]
+        #[should_panic(expected = "only supports dense layers")]
+        fn test_recurrent() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut topology = topology(3, Activation::None);
+            topology[1].kind = LayerKind::Elman;
+
+            let network = Network::random(&mut rng, &topology);
+            let mut gradient = vec![0.0; Network::parameters(&topology)];
+
+            network.backpropagate(
+                &[0.0, 0.0],
+                &[0.0, 0.0],
+                Loss::MeanSquaredError,
+                &mut gradient,
+            );
+        }
+    }
+
+    mod train {
+        use super::*;
+
+        /// XOR on the first output and its negation on the second
+        fn xor() -> Vec<Sample> {
+            vec![
+                Sample::new(vec![0.0, 0.0], vec![0.0, 1.0]),
+                Sample::new(vec![0.0, 1.0], vec![1.0, 0.0]),
+                Sample::new(vec![1.0, 0.0], vec![1.0, 0.0]),
+                Sample::new(vec![1.0, 1.0], vec![0.0, 1.0]),
+            ]
+        }
+
+        fn assert_learns(output: Activation, trainer: Trainer) {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut network = Network::random(&mut rng, &topology(8, output));
+
+            let losses = trainer.train(&mut rng, &mut network, &xor());
+            assert_eq!(losses.len(), trainer.epochs);
+            assert!(losses[losses.len() - 1] < losses[0] / 10.0);
+
+            for sample in xor() {
+                let outputs = network.propagate(sample.inputs);
+                let predicted = if outputs[0] > outputs[1] { 1.0 } else { 0.0 };
+
+                assert_eq!(predicted, sample.targets[0]);
+            }
+        }
+
+        #[test]
+        fn test_sgd() {
+            assert_learns(
+                Activation::Sigmoid,
+                Trainer::new(
+                    Loss::MeanSquaredError,
+                    Optimizer::Sgd {
+                        learning_rate: 0.5,
+                        momentum: 0.9,
+                    },
+                    1000,
+                    4,
+                ),
+            );
+        }
+
+        #[test]
+        fn test_adam() {
+            assert_learns(
+                Activation::Softmax,
+                Trainer::new(Loss::CrossEntropy, Optimizer::adam(0.05), 300, 2),
+            );
+        }
+    }
+}
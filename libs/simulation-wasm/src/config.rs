@@ -1,6 +1,9 @@
+use std::str::FromStr;
+
 use crate::*;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 #[wasm_bindgen(inspectable)]
 pub struct Config {
     pub population_count: usize,
@@ -10,25 +13,190 @@ pub struct Config {
     pub food_size: f32,
     pub starting_energy: f32,
     pub food_energy: f32,
-    pub energy_loss_factor: f32,
     pub reproduction_cost: f32,
     pub reproduction_threshold: f32,
-    pub speed_min: f32,
     pub speed_max: f32,
-    pub speed_accel: f32,
-    pub rotation_accel: f32,
+    pub angular_speed_max: f32,
+    pub max_thrust: f32,
+    pub max_torque: f32,
+    pub linear_drag: f32,
+    pub angular_drag: f32,
+    pub dt: f32,
     pub mutation_rate: f32,
     pub mutation_strength: f32,
     pub fov_range: f32,
     pub fov_angle: f32,
+    pub fov_range_min: f32,
+    pub fov_range_max: f32,
+    pub fov_angle_min: f32,
+    pub fov_angle_max: f32,
     pub eye_cells: usize,
+    pub terrain_resolution: usize,
+    pub terrain_variation: f32,
+    pub terrain_temperature_cost: f32,
+    pub pheromone_channels: usize,
+    pub pheromone_resolution: usize,
+    pub pheromone_diffusion: f32,
+    pub pheromone_evaporation: f32,
+    pub pheromone_deposit: f32,
+    pub pheromone_antenna: f32,
+    pub signal_channels: usize,
+    pub signal_range: f32,
+    pub signal_color: bool,
+    pub max_lifespan: usize,
+    pub senescence_onset: f32,
+    pub senescence_energy_factor: f32,
+    pub senescence_speed_factor: f32,
+    pub basal_cost: f32,
+    pub movement_cost: f32,
+    pub rotation_cost: f32,
+    pub brain_cost: f32,
+    pub vision_cost: f32,
+    pub energy_capacity: f32,
+    pub creature_collisions: bool,
+    pub collision_elasticity: f32,
+    pub sense_energy: bool,
+    pub sense_speed: bool,
+    pub sense_age: bool,
+    pub sense_walls: bool,
+    pub sense_clock: bool,
+    pub clock_period: usize,
+    #[wasm_bindgen(skip)]
+    pub brain_hidden_layers: Vec<usize>,
+    #[wasm_bindgen(skip)]
+    pub brain_hidden_activations: Vec<String>,
+    #[wasm_bindgen(skip)]
+    pub brain_hidden_kinds: Vec<String>,
+    #[wasm_bindgen(skip)]
+    pub brain_hidden_initializers: Vec<String>,
+
+    #[wasm_bindgen(skip)]
+    pub brain_output_activation: String,
+    #[wasm_bindgen(skip)]
+    pub brain_output_initializer: String,
+    #[wasm_bindgen(skip)]
+    pub brain_kind: String,
+    pub neat_add_node_rate: f32,
+    pub neat_add_connection_rate: f32,
+    pub neat_toggle_rate: f32,
+    pub neat_activation_rate: f32,
+    #[wasm_bindgen(skip)]
+    pub neat_activations: Vec<String>,
+    pub ctrnn_neurons: usize,
+    pub ctrnn_dt: f32,
+    pub brain_plasticity: bool,
+    pub plasticity_baseline: f32,
+    pub plasticity_reward: f32,
+    #[wasm_bindgen(skip)]
+    pub brain_compression: String,
+    pub brain_prune_threshold: f32,
+    pub diversity_interval: usize,
+    pub diversity_sample: usize,
+    pub diversity_probes: usize,
+    pub novelty_weight: f32,
+    pub novelty_neighbours: usize,
+    pub novelty_threshold: f32,
+    pub novelty_archive: usize,
 }
 
 #[wasm_bindgen]
 impl Config {
     #[wasm_bindgen(constructor)]
     pub fn from_object(obj: &JsValue) -> Config {
-        obj.into_serde().unwrap_or_default()
+        serde_wasm_bindgen::from_value(obj.clone()).unwrap_or_default()
+    }
+
+    // Brain settings aren't plain numbers, so they're exposed through
+    // accessors rather than fields
+
+    #[wasm_bindgen(getter)]
+    pub fn brain_hidden_layers(&self) -> Vec<usize> {
+        self.brain_hidden_layers.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_brain_hidden_layers(&mut self, layers: Vec<usize>) {
+        self.brain_hidden_layers = layers;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn brain_hidden_activations(&self) -> Vec<String> {
+        self.brain_hidden_activations.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_brain_hidden_activations(&mut self, activations: Vec<String>) {
+        self.brain_hidden_activations = activations;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn brain_hidden_kinds(&self) -> Vec<String> {
+        self.brain_hidden_kinds.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_brain_hidden_kinds(&mut self, kinds: Vec<String>) {
+        self.brain_hidden_kinds = kinds;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn brain_hidden_initializers(&self) -> Vec<String> {
+        self.brain_hidden_initializers.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_brain_hidden_initializers(&mut self, initializers: Vec<String>) {
+        self.brain_hidden_initializers = initializers;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn brain_output_activation(&self) -> String {
+        self.brain_output_activation.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_brain_output_activation(&mut self, activation: String) {
+        self.brain_output_activation = activation;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn brain_output_initializer(&self) -> String {
+        self.brain_output_initializer.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_brain_output_initializer(&mut self, initializer: String) {
+        self.brain_output_initializer = initializer;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn brain_kind(&self) -> String {
+        self.brain_kind.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_brain_kind(&mut self, kind: String) {
+        self.brain_kind = kind;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn brain_compression(&self) -> String {
+        self.brain_compression.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_brain_compression(&mut self, compression: String) {
+        self.brain_compression = compression;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn neat_activations(&self) -> Vec<String> {
+        self.neat_activations.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_neat_activations(&mut self, activations: Vec<String>) {
+        self.neat_activations = activations;
     }
 }
 
@@ -42,45 +210,234 @@ impl From<sim::Config> for Config {
             food_size: config.food_size,
             starting_energy: config.starting_energy,
             food_energy: config.food_energy,
-            energy_loss_factor: config.energy_loss_factor,
             reproduction_cost: config.reproduction_cost,
             reproduction_threshold: config.reproduction_threshold,
-            speed_min: config.speed_min,
             speed_max: config.speed_max,
-            speed_accel: config.speed_accel,
-            rotation_accel: config.rotation_accel,
+            angular_speed_max: config.angular_speed_max,
+            max_thrust: config.max_thrust,
+            max_torque: config.max_torque,
+            linear_drag: config.linear_drag,
+            angular_drag: config.angular_drag,
+            dt: config.dt,
             mutation_rate: config.mutation_rate,
             mutation_strength: config.mutation_strength,
             fov_range: config.fov_range,
             fov_angle: config.fov_angle,
+            fov_range_min: config.fov_range_min,
+            fov_range_max: config.fov_range_max,
+            fov_angle_min: config.fov_angle_min,
+            fov_angle_max: config.fov_angle_max,
             eye_cells: config.eye_cells,
+            terrain_resolution: config.terrain_resolution,
+            terrain_variation: config.terrain_variation,
+            terrain_temperature_cost: config.terrain_temperature_cost,
+            pheromone_channels: config.pheromone_channels,
+            pheromone_resolution: config.pheromone_resolution,
+            pheromone_diffusion: config.pheromone_diffusion,
+            pheromone_evaporation: config.pheromone_evaporation,
+            pheromone_deposit: config.pheromone_deposit,
+            pheromone_antenna: config.pheromone_antenna,
+            signal_channels: config.signal_channels,
+            signal_range: config.signal_range,
+            signal_color: config.signal_color,
+            max_lifespan: config.max_lifespan,
+            senescence_onset: config.senescence_onset,
+            senescence_energy_factor: config.senescence_energy_factor,
+            senescence_speed_factor: config.senescence_speed_factor,
+            basal_cost: config.basal_cost,
+            movement_cost: config.movement_cost,
+            rotation_cost: config.rotation_cost,
+            brain_cost: config.brain_cost,
+            vision_cost: config.vision_cost,
+            energy_capacity: config.energy_capacity,
+            creature_collisions: config.creature_collisions,
+            collision_elasticity: config.collision_elasticity,
+            sense_energy: config.sense_energy,
+            sense_speed: config.sense_speed,
+            sense_age: config.sense_age,
+            sense_walls: config.sense_walls,
+            sense_clock: config.sense_clock,
+            clock_period: config.clock_period,
+            brain_hidden_layers: config
+                .brain_hidden_layers
+                .iter()
+                .map(|layer| layer.neurons)
+                .collect(),
+            brain_hidden_activations: config
+                .brain_hidden_layers
+                .iter()
+                .map(|layer| layer.activation.to_string())
+                .collect(),
+            brain_hidden_kinds: config
+                .brain_hidden_layers
+                .iter()
+                .map(|layer| layer.kind.to_string())
+                .collect(),
+            brain_hidden_initializers: config
+                .brain_hidden_layers
+                .iter()
+                .map(|layer| layer.initializer.to_string())
+                .collect(),
+            brain_output_activation: config.brain_output_activation.to_string(),
+            brain_output_initializer: config.brain_output_initializer.to_string(),
+            brain_kind: config.brain_kind.to_string(),
+            neat_add_node_rate: config.neat_add_node_rate,
+            neat_add_connection_rate: config.neat_add_connection_rate,
+            neat_toggle_rate: config.neat_toggle_rate,
+            neat_activation_rate: config.neat_activation_rate,
+            neat_activations: config
+                .neat_activations
+                .iter()
+                .map(|activation| activation.to_string())
+                .collect(),
+            ctrnn_neurons: config.ctrnn_neurons,
+            ctrnn_dt: config.ctrnn_dt,
+            brain_plasticity: config.brain_plasticity,
+            plasticity_baseline: config.plasticity_baseline,
+            plasticity_reward: config.plasticity_reward,
+            brain_compression: config.brain_compression.to_string(),
+            brain_prune_threshold: config.brain_prune_threshold,
+            diversity_interval: config.diversity_interval,
+            diversity_sample: config.diversity_sample,
+            diversity_probes: config.diversity_probes,
+            novelty_weight: config.novelty_weight,
+            novelty_neighbours: config.novelty_neighbours,
+            novelty_threshold: config.novelty_threshold,
+            novelty_archive: config.novelty_archive,
         }
     }
 }
 
-impl Into<sim::Config> for Config {
-    fn into(self) -> sim::Config {
-        sim::Config {
-            population_count: self.population_count,
-            target_population: self.target_population,
-            food_count: self.food_count,
-            creature_size: self.creature_size,
-            food_size: self.food_size,
-            starting_energy: self.starting_energy,
-            food_energy: self.food_energy,
-            energy_loss_factor: self.energy_loss_factor,
-            reproduction_cost: self.reproduction_cost,
-            reproduction_threshold: self.reproduction_threshold,
-            speed_min: self.speed_min,
-            speed_max: self.speed_max,
-            speed_accel: self.speed_accel,
-            rotation_accel: self.rotation_accel,
-            mutation_rate: self.mutation_rate,
-            mutation_strength: self.mutation_strength,
-            fov_range: self.fov_range,
-            fov_angle: self.fov_angle,
-            eye_cells: self.eye_cells,
+/// Fails on settings JS got wrong, e.g. a misspelled activation, fewer
+/// activations than hidden layers or anything `sim::Config::validate` rejects
+impl TryFrom<Config> for sim::Config {
+    type Error = String;
+
+    fn try_from(config: Config) -> Result<sim::Config, String> {
+        if config.brain_hidden_layers.len() != config.brain_hidden_activations.len() {
+            return Err(format!(
+                "Every brain hidden layer needs an activation, got {} layers and {} activations",
+                config.brain_hidden_layers.len(),
+                config.brain_hidden_activations.len()
+            ));
         }
+
+        let brain_hidden_layers: Vec<_> = config
+            .brain_hidden_layers
+            .iter()
+            .zip(&config.brain_hidden_activations)
+            .enumerate()
+            .map(|(idx, (neurons, activation))| {
+                Ok(sim::LayerTopology {
+                    neurons: *neurons,
+                    activation: parse("brain_hidden_activations", activation)?,
+                    kind: match config.brain_hidden_kinds.get(idx) {
+                        Some(kind) => parse("brain_hidden_kinds", kind)?,
+                        None => Default::default(),
+                    },
+                    initializer: match config.brain_hidden_initializers.get(idx) {
+                        Some(initializer) => parse("brain_hidden_initializers", initializer)?,
+                        None => Default::default(),
+                    },
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        let neat_activations = config
+            .neat_activations
+            .iter()
+            .map(|activation| parse("neat_activations", activation))
+            .collect::<Result<_, String>>()?;
+
+        let config = sim::Config {
+            population_count: config.population_count,
+            target_population: config.target_population,
+            food_count: config.food_count,
+            creature_size: config.creature_size,
+            food_size: config.food_size,
+            starting_energy: config.starting_energy,
+            food_energy: config.food_energy,
+            reproduction_cost: config.reproduction_cost,
+            reproduction_threshold: config.reproduction_threshold,
+            speed_max: config.speed_max,
+            angular_speed_max: config.angular_speed_max,
+            max_thrust: config.max_thrust,
+            max_torque: config.max_torque,
+            linear_drag: config.linear_drag,
+            angular_drag: config.angular_drag,
+            dt: config.dt,
+            mutation_rate: config.mutation_rate,
+            mutation_strength: config.mutation_strength,
+            fov_range: config.fov_range,
+            fov_angle: config.fov_angle,
+            fov_range_min: config.fov_range_min,
+            fov_range_max: config.fov_range_max,
+            fov_angle_min: config.fov_angle_min,
+            fov_angle_max: config.fov_angle_max,
+            eye_cells: config.eye_cells,
+            terrain_resolution: config.terrain_resolution,
+            terrain_variation: config.terrain_variation,
+            terrain_temperature_cost: config.terrain_temperature_cost,
+            pheromone_channels: config.pheromone_channels,
+            pheromone_resolution: config.pheromone_resolution,
+            pheromone_diffusion: config.pheromone_diffusion,
+            pheromone_evaporation: config.pheromone_evaporation,
+            pheromone_deposit: config.pheromone_deposit,
+            pheromone_antenna: config.pheromone_antenna,
+            signal_channels: config.signal_channels,
+            signal_range: config.signal_range,
+            signal_color: config.signal_color,
+            max_lifespan: config.max_lifespan,
+            senescence_onset: config.senescence_onset,
+            senescence_energy_factor: config.senescence_energy_factor,
+            senescence_speed_factor: config.senescence_speed_factor,
+            basal_cost: config.basal_cost,
+            movement_cost: config.movement_cost,
+            rotation_cost: config.rotation_cost,
+            brain_cost: config.brain_cost,
+            vision_cost: config.vision_cost,
+            energy_capacity: config.energy_capacity,
+            creature_collisions: config.creature_collisions,
+            collision_elasticity: config.collision_elasticity,
+            sense_energy: config.sense_energy,
+            sense_speed: config.sense_speed,
+            sense_age: config.sense_age,
+            sense_walls: config.sense_walls,
+            sense_clock: config.sense_clock,
+            clock_period: config.clock_period,
+            brain_hidden_layers,
+            brain_output_activation: parse(
+                "brain_output_activation",
+                &config.brain_output_activation,
+            )?,
+            brain_output_initializer: parse(
+                "brain_output_initializer",
+                &config.brain_output_initializer,
+            )?,
+            brain_kind: parse("brain_kind", &config.brain_kind)?,
+            neat_add_node_rate: config.neat_add_node_rate,
+            neat_add_connection_rate: config.neat_add_connection_rate,
+            neat_toggle_rate: config.neat_toggle_rate,
+            neat_activation_rate: config.neat_activation_rate,
+            neat_activations,
+            ctrnn_neurons: config.ctrnn_neurons,
+            ctrnn_dt: config.ctrnn_dt,
+            brain_plasticity: config.brain_plasticity,
+            plasticity_baseline: config.plasticity_baseline,
+            plasticity_reward: config.plasticity_reward,
+            brain_compression: parse("brain_compression", &config.brain_compression)?,
+            brain_prune_threshold: config.brain_prune_threshold,
+            diversity_interval: config.diversity_interval,
+            diversity_sample: config.diversity_sample,
+            diversity_probes: config.diversity_probes,
+            novelty_weight: config.novelty_weight,
+            novelty_neighbours: config.novelty_neighbours,
+            novelty_threshold: config.novelty_threshold,
+            novelty_archive: config.novelty_archive,
+        };
+
+        config.validate()?;
+        Ok(config)
     }
 }
 
@@ -89,3 +446,12 @@ impl Default for Config {
         Config::from(sim::Config::default())
     }
 }
+
+fn parse<T>(field: &str, value: &str) -> Result<T, String>
+where
+    T: FromStr<Err = String>,
+{
+    value
+        .parse()
+        .map_err(|err| format!("Invalid {}: {}", field, err))
+}
@@ -13,6 +13,7 @@ pub struct Simulation {
     rng: ThreadRng,
     sim: sim::Simulation,
     pub generation: usize,
+    #[wasm_bindgen(getter_with_clone)]
     pub config: Config,
 }
 
@@ -29,11 +30,29 @@ pub struct Creature {
     pub x: f32,
     pub y: f32,
     pub rotation: f32,
+    pub speed: f32,
     pub fitness: f32,
     pub energy: f32,
     pub size: f32,
     pub color: f32,
     pub generation: usize,
+    pub age: usize,
+    pub fov_range: f32,
+    pub fov_angle: f32,
+    #[wasm_bindgen(skip)]
+    pub signal: Vec<f32>,
+    pub metabolism: Metabolism,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[wasm_bindgen]
+pub struct Metabolism {
+    pub basal: f32,
+    pub movement: f32,
+    pub rotation: f32,
+    pub brain: f32,
+    pub vision: f32,
+    pub total: f32,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -43,31 +62,86 @@ pub struct Food {
     pub y: f32,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct Terrain {
+    pub resolution: usize,
+    pub friction: Vec<f32>,
+    pub temperature: Vec<f32>,
+    pub fertility: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Pheromones {
+    pub resolution: usize,
+    pub channels: Vec<Vec<f32>>,
+}
+
+/// Activations of a creature's brain, from its senses to its actions
+#[derive(Debug, Clone, Serialize)]
+pub struct BrainTrace {
+    pub inputs: Vec<f32>,
+    pub hidden: Vec<Vec<f32>>, // One entry per hidden layer
+    pub outputs: Vec<f32>,
+}
+
+/// How varied the population is; both measures fall as it converges
+#[derive(Debug, Clone, Copy, Serialize)]
+#[wasm_bindgen]
+pub struct Diversity {
+    pub genetic: f32,
+    pub behavioural: f32,
+}
+
 #[wasm_bindgen]
 impl Simulation {
+    /// Throws when the config can't be understood, e.g. an unknown activation
     #[wasm_bindgen(constructor)]
-    pub fn new(config: Option<Config>) -> Simulation {
+    pub fn new(config: Option<Config>) -> Result<Simulation, JsError> {
         let mut rng = thread_rng();
         let config = config.unwrap_or_default();
-        let sim = sim::Simulation::random(&mut rng, Some(config.into()));
+        let sim_config = sim::Config::try_from(config.clone()).map_err(|err| JsError::new(&err))?;
+        let sim = sim::Simulation::random(&mut rng, Some(sim_config));
 
-        Simulation {
+        Ok(Simulation {
             rng,
             sim,
             generation: 0,
             config,
-        }
+        })
     }
 
     pub fn world(&self) -> JsValue {
         let world = World::from(self.sim.world());
-        JsValue::from_serde(&world).unwrap()
+        serde_wasm_bindgen::to_value(&world).unwrap()
+    }
+
+    pub fn terrain(&self) -> JsValue {
+        let terrain = Terrain::from(self.sim.world().terrain());
+        serde_wasm_bindgen::to_value(&terrain).unwrap()
+    }
+
+    pub fn pheromones(&self) -> JsValue {
+        let pheromones = Pheromones::from(self.sim.world().pheromones());
+        serde_wasm_bindgen::to_value(&pheromones).unwrap()
+    }
+
+    /// Brain activity the creature with the given `id` is about to act on,
+    /// or null if there's no such creature
+    pub fn brain_trace(&self, id: usize) -> JsValue {
+        match self.sim.brain_trace(id) {
+            Some(trace) => serde_wasm_bindgen::to_value(&BrainTrace::from(trace)).unwrap(),
+            None => JsValue::NULL,
+        }
     }
 
     pub fn age(&self) -> usize {
         *self.sim.age()
     }
 
+    pub fn diversity(&self) -> Diversity {
+        Diversity::from(self.sim.diversity())
+    }
+
     pub fn step(&mut self) {
         self.sim.step(&mut self.rng)
     }
@@ -89,11 +163,17 @@ impl From<&sim::World> for World {
                 x: creature.x,
                 y: creature.y,
                 rotation: creature.rotation,
+                speed: creature.speed,
                 fitness: creature.fitness,
                 energy: creature.energy,
                 size: creature.size,
                 color: creature.color,
                 generation: creature.generation,
+                age: creature.age,
+                fov_range: creature.fov_range,
+                fov_angle: creature.fov_angle,
+                signal: creature.signal,
+                metabolism: creature.metabolism,
             })
             .collect();
         let foods = world.foods().iter().map(Food::from).collect();
@@ -102,6 +182,32 @@ impl From<&sim::World> for World {
     }
 }
 
+impl From<Vec<Vec<f32>>> for BrainTrace {
+    fn from(mut trace: Vec<Vec<f32>>) -> BrainTrace {
+        let outputs = trace.pop().unwrap_or_default();
+        let inputs = if trace.is_empty() {
+            Vec::new()
+        } else {
+            trace.remove(0)
+        };
+
+        BrainTrace {
+            inputs,
+            hidden: trace,
+            outputs,
+        }
+    }
+}
+
+impl From<&sim::Diversity> for Diversity {
+    fn from(diversity: &sim::Diversity) -> Diversity {
+        Diversity {
+            genetic: diversity.genetic(),
+            behavioural: diversity.behavioural(),
+        }
+    }
+}
+
 impl From<&sim::Creature> for Creature {
     fn from(creature: &sim::Creature) -> Creature {
         Creature {
@@ -109,11 +215,17 @@ impl From<&sim::Creature> for Creature {
             x: creature.position().x,
             y: creature.position().y,
             rotation: creature.rotation().angle(),
+            speed: creature.speed(),
             fitness: creature.fitness(),
             energy: creature.energy(),
             size: creature.size(),
             color: creature.color(),
             generation: creature.generation(),
+            age: creature.age(),
+            fov_range: creature.eye().fov_range(),
+            fov_angle: creature.eye().fov_angle(),
+            signal: creature.signal().to_vec(),
+            metabolism: Metabolism::from(creature.energy_report()),
         }
     }
 }
@@ -126,3 +238,40 @@ impl From<&sim::Food> for Food {
         }
     }
 }
+
+impl From<&sim::Terrain> for Terrain {
+    fn from(terrain: &sim::Terrain) -> Terrain {
+        let cells = terrain.cells();
+
+        Terrain {
+            resolution: terrain.resolution(),
+            friction: cells.iter().map(|cell| cell.friction()).collect(),
+            temperature: cells.iter().map(|cell| cell.temperature()).collect(),
+            fertility: cells.iter().map(|cell| cell.fertility()).collect(),
+        }
+    }
+}
+
+impl From<&sim::PheromoneField> for Pheromones {
+    fn from(pheromones: &sim::PheromoneField) -> Pheromones {
+        Pheromones {
+            resolution: pheromones.resolution(),
+            channels: (0..pheromones.channels())
+                .map(|channel| pheromones.values(channel).to_vec())
+                .collect(),
+        }
+    }
+}
+
+impl From<&sim::EnergyReport> for Metabolism {
+    fn from(report: &sim::EnergyReport) -> Metabolism {
+        Metabolism {
+            basal: report.basal(),
+            movement: report.movement(),
+            rotation: report.rotation(),
+            brain: report.brain(),
+            vision: report.vision(),
+            total: report.total(),
+        }
+    }
+}
@@ -4,10 +4,13 @@ use crate::*;
 pub struct Body {
     pub(crate) size: f32,
     pub(crate) color: f32,
-    // Could replace rotation and speed with velocity Vector2
     pub(crate) rotation: na::Rotation2<f32>,
-    pub(crate) speed: f32,
+    pub(crate) velocity: na::Vector2<f32>,
+    pub(crate) angular_velocity: f32,
+    pub(crate) thrust: f32, // Forward force requested by the Brain [-1, 1]
+    pub(crate) torque: f32, // Turning force requested by the Brain [-1, 1]
     pub(crate) energy: f32,
+    pub(crate) energy_report: EnergyReport, // Energy spent during the last tick
 }
 
 impl Body {
@@ -15,13 +18,20 @@ impl Body {
         assert!(size > 0.0);
         assert!(color >= 0.0);
 
-        Body {
+        let mut body = Body {
             size,
             color,
             rotation: rng.gen(),
-            speed: config.speed_min,
-            energy: config.starting_energy,
-        }
+            velocity: na::Vector2::zeros(),
+            angular_velocity: 0.0,
+            thrust: 0.0,
+            torque: 0.0,
+            energy: 0.0,
+            energy_report: EnergyReport::default(),
+        };
+        body.energy = config.starting_energy.min(body.energy_capacity(config));
+
+        body
     }
 
     pub(crate) fn random(rng: &mut dyn RngCore, config: &Config) -> Body {
@@ -31,25 +41,69 @@ impl Body {
         Body::new(rng, size, color, config)
     }
 
-    pub(crate) fn as_chromosome(&self) -> ga::Chromosome {
-        ga::Chromosome::from_iter(vec![self.size * 10.0, self.color * 5.0])
+    /// Heritable traits, in the order of the genome's "body" segment
+    pub(crate) fn traits(&self) -> Vec<f32> {
+        vec![self.size, self.color]
+    }
+
+    pub(crate) fn from_traits(traits: &[f32], rng: &mut dyn RngCore, config: &Config) -> Body {
+        Body::new(rng, traits[0], traits[1], config)
+    }
+
+    /// Integrates thrust, torque and drag over one timestep, returning the displacement.
+    /// `traction` scales the usable thrust, e.g. to account for terrain friction.
+    pub(crate) fn process_physics(&mut self, traction: f32, config: &Config) -> na::Vector2<f32> {
+        let mass = self.mass(config);
+        let inertia = mass * (self.size / config.creature_size).powi(2);
+
+        let force =
+            self.rotation * na::Vector2::new(traction * self.thrust * config.max_thrust, 0.0);
+        self.velocity += (force / mass - config.linear_drag * self.velocity) * config.dt;
+        self.velocity = self.velocity.cap_magnitude(config.speed_max);
+
+        let torque = self.torque * config.max_torque;
+        self.angular_velocity +=
+            (torque / inertia - config.angular_drag * self.angular_velocity) * config.dt;
+        self.angular_velocity = self
+            .angular_velocity
+            .clamp(-config.angular_speed_max, config.angular_speed_max);
+        self.rotation =
+            na::Rotation2::new(self.rotation.angle() + self.angular_velocity * config.dt);
+
+        self.velocity * config.dt
     }
 
-    pub(crate) fn from_chromosome(
-        chromosome: ga::Chromosome,
-        rng: &mut dyn RngCore,
+    pub(crate) fn process_energy(
+        &mut self,
+        brain: &Brain,
+        eye: &Eye,
+        terrain: &TerrainCell,
+        senescence: f32,
         config: &Config,
-    ) -> Body {
-        let size =
-            (chromosome[0] / 10.0).clamp(config.creature_size / 5.0, config.creature_size * 5.0);
-        let color = (chromosome[1] / 5.0).clamp(0.0, 1.0);
+    ) {
+        let factor = terrain.metabolism_factor(config)
+            * (1.0 + config.senescence_energy_factor * senescence);
 
-        Body::new(rng, size, color, config)
+        self.energy_report = EnergyReport::new(self, brain, eye, factor, config);
+        self.energy -= self.energy_report.total();
+    }
+
+    /// Gains energy, up to the capacity of the Body
+    pub(crate) fn eat(&mut self, energy: f32, config: &Config) {
+        self.energy = (self.energy + energy).min(self.energy_capacity(config));
     }
 
-    pub(crate) fn process_energy(&mut self, rotation: f32, config: &Config) {
-        self.energy -= config.energy_loss_factor
-            * (rotation.abs() + self.speed + (10.0 * self.size)).powf(2.0);
+    /// Mass relative to a Creature of the default size
+    pub fn mass(&self, config: &Config) -> f32 {
+        (self.size / config.creature_size).powi(2)
+    }
+
+    /// Maximum energy the Body can store, growing linearly with size but
+    /// never below the reproduction threshold, so that small creatures can
+    /// still reproduce
+    pub fn energy_capacity(&self, config: &Config) -> f32 {
+        (config.energy_capacity * self.size / config.creature_size)
+            .max(config.reproduction_threshold)
     }
 
     pub fn size(&self) -> f32 {
@@ -64,7 +118,162 @@ impl Body {
         self.rotation
     }
 
+    pub fn velocity(&self) -> na::Vector2<f32> {
+        self.velocity
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.velocity.norm()
+    }
+
+    pub fn angular_velocity(&self) -> f32 {
+        self.angular_velocity
+    }
+
     pub fn energy(&self) -> f32 {
         self.energy
     }
+
+    pub fn energy_report(&self) -> &EnergyReport {
+        &self.energy_report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn config() -> Config {
+        Config {
+            creature_size: 0.01,
+            speed_max: 1.0,
+            max_thrust: 0.01,
+            max_torque: 0.1,
+            linear_drag: 0.1,
+            angular_drag: 0.5,
+            angular_speed_max: 1.0,
+            dt: 1.0,
+            ..Default::default()
+        }
+    }
+
+    fn body(size: f32, config: &Config) -> Body {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut body = Body::new(&mut rng, size, 0.5, config);
+        body.rotation = na::Rotation2::new(0.0);
+        body
+    }
+
+    mod process_physics {
+        use super::*;
+
+        #[test]
+        fn test_reaches_terminal_velocity() {
+            let config = config();
+            let mut body = body(0.01, &config);
+            body.thrust = 1.0;
+
+            for _ in 0..500 {
+                body.process_physics(1.0, &config);
+            }
+
+            // Thrust is balanced by drag once v = F / (m * drag)
+            assert_relative_eq!(body.velocity.x, 0.1, epsilon = 1e-4);
+            assert_relative_eq!(body.velocity.y, 0.0);
+        }
+
+        #[test]
+        fn test_heavier_bodies_accelerate_slower() {
+            let config = config();
+            let mut light = body(0.01, &config);
+            let mut heavy = body(0.02, &config);
+            light.thrust = 1.0;
+            heavy.thrust = 1.0;
+
+            let light_displacement = light.process_physics(1.0, &config);
+            let heavy_displacement = heavy.process_physics(1.0, &config);
+
+            assert_relative_eq!(light_displacement.x, 0.01);
+            assert_relative_eq!(heavy_displacement.x, 0.0025);
+        }
+
+        #[test]
+        fn test_drag_slows_coasting_bodies() {
+            let config = config();
+            let mut body = body(0.01, &config);
+            body.velocity = na::Vector2::new(0.0, 0.5);
+
+            body.process_physics(1.0, &config);
+            assert_relative_eq!(body.velocity.y, 0.45);
+
+            body.process_physics(1.0, &config);
+            assert_relative_eq!(body.velocity.y, 0.405);
+        }
+
+        #[test]
+        fn test_traction_limits_thrust() {
+            let config = config();
+            let mut body = body(0.01, &config);
+            body.thrust = 1.0;
+
+            let displacement = body.process_physics(0.25, &config);
+            assert_relative_eq!(displacement.x, 0.0025);
+        }
+
+        #[test]
+        fn test_torque_turns_body() {
+            let config = config();
+            let mut body = body(0.01, &config);
+            body.torque = -1.0;
+
+            body.process_physics(1.0, &config);
+            assert_relative_eq!(body.angular_velocity, -0.1);
+            assert_relative_eq!(body.rotation.angle(), -0.1);
+
+            body.torque = 0.0;
+            body.process_physics(1.0, &config);
+            assert_relative_eq!(body.angular_velocity, -0.05);
+            assert_relative_eq!(body.rotation.angle(), -0.15);
+        }
+
+        #[test]
+        fn test_speed_is_capped() {
+            let config = Config {
+                speed_max: 0.005,
+                ..config()
+            };
+            let mut body = body(0.01, &config);
+            body.thrust = 1.0;
+
+            body.process_physics(1.0, &config);
+            assert_relative_eq!(body.speed(), 0.005);
+        }
+    }
+
+    mod energy_capacity {
+        use super::*;
+
+        #[test]
+        fn test_grows_with_size() {
+            let config = config();
+
+            assert_relative_eq!(body(0.01, &config).energy_capacity(&config), 300.0);
+            assert_relative_eq!(body(0.02, &config).energy_capacity(&config), 600.0);
+        }
+
+        #[test]
+        fn test_small_bodies_can_reproduce() {
+            let config = config();
+            let mut body = body(0.002, &config);
+
+            for _ in 0..10 {
+                body.eat(config.food_energy, &config);
+            }
+
+            assert!(body.energy >= config.reproduction_threshold);
+        }
+    }
 }
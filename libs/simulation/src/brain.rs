@@ -1,43 +1,930 @@
-use lib_neural_network::Activation;
+use std::fmt;
+use std::iter::once;
+use std::str::FromStr;
 
 use crate::*;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrainKind {
+    /// Topology fixed by the config, with weights evolved through the chromosome
+    Layered,
+    /// Topology and weights evolved together by NEAT
+    Neat,
+    /// Fully connected continuous-time neurons with evolved time constants
+    Ctrnn,
+}
+
+impl fmt::Display for BrainKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match *self {
+            BrainKind::Layered => "layered",
+            BrainKind::Neat => "neat",
+            BrainKind::Ctrnn => "ctrnn",
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl FromStr for BrainKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<BrainKind, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "layered" => Ok(BrainKind::Layered),
+            "neat" => Ok(BrainKind::Neat),
+            "ctrnn" => Ok(BrainKind::Ctrnn),
+            _ => Err(format!("Unknown brain kind: {}", name)),
+        }
+    }
+}
+
+/// How layered brains run once decoded. The chromosome always carries every
+/// float weight, so compression only changes how a creature's own brain runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrainCompression {
+    /// Every weight, as a float
+    None,
+    /// Weights below `brain_prune_threshold` dropped, leaving a sparse network
+    Pruned,
+    /// Int8 weights and inputs, calibrated on inputs of ±1
+    Quantized,
+}
+
+impl fmt::Display for BrainCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match *self {
+            BrainCompression::None => "none",
+            BrainCompression::Pruned => "pruned",
+            BrainCompression::Quantized => "quantized",
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl FromStr for BrainCompression {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<BrainCompression, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" => Ok(BrainCompression::None),
+            "pruned" => Ok(BrainCompression::Pruned),
+            "quantized" => Ok(BrainCompression::Quantized),
+            _ => Err(format!("Unknown brain compression: {}", name)),
+        }
+    }
+}
+
+/// Brains whose weights are carried by the chromosome, unlike NEAT ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EncodedBrain {
+    Layered,
+    Plastic,
+    Ctrnn,
+}
+
+/// How a child gets its brain: NEAT brains are bred directly, while every
+/// other brain is decoded from the child's chromosome
+#[derive(Debug, Clone)]
+pub(crate) enum Inheritance {
+    Bred(Brain),
+    Encoded(EncodedBrain),
+}
+
 #[derive(Debug, Clone)]
 pub struct Brain {
-    pub(crate) nn: nn::Network,
+    pub(crate) nn: BrainNetwork,
 }
 
-impl Brain {
-    pub fn random(rng: &mut dyn RngCore, eye: &Eye) -> Brain {
-        Brain {
-            nn: nn::Network::random(rng, &Self::topology(eye)),
+#[derive(Debug, Clone)]
+pub(crate) enum BrainNetwork {
+    Layered(nn::MatrixNetwork),
+    Neat {
+        genome: neat::Genome,
+        network: neat::Network,
+    },
+    Ctrnn(nn::Ctrnn),
+    Plastic(nn::PlasticNetwork),
+    /// Layered brain running on a compressed copy of its weights
+    Pruned {
+        genes: nn::MatrixNetwork,
+        network: nn::SparseNetwork,
+    },
+    Quantized {
+        genes: nn::MatrixNetwork,
+        network: nn::QuantizedNetwork,
+    },
+}
+
+impl BrainNetwork {
+    /// Layered brain, compressed as the config asks
+    fn layered(genes: nn::MatrixNetwork, config: &Config) -> BrainNetwork {
+        match config.brain_compression {
+            BrainCompression::None => BrainNetwork::Layered(genes),
+            BrainCompression::Pruned => {
+                let mut network = nn::Network::from(&genes);
+                network.prune(config.brain_prune_threshold);
+
+                BrainNetwork::Pruned {
+                    network: nn::SparseNetwork::from(&network),
+                    genes,
+                }
+            }
+            BrainCompression::Quantized => {
+                let samples = [vec![1.0; genes.inputs()], vec![-1.0; genes.inputs()]];
+
+                BrainNetwork::Quantized {
+                    network: nn::QuantizedNetwork::calibrate(&nn::Network::from(&genes), &samples),
+                    genes,
+                }
+            }
         }
     }
 
-    pub(crate) fn as_chromosome(&self) -> ga::Chromosome {
-        self.nn.weights().collect()
+    /// Float weights of a layered brain, compressed or not
+    fn genes(&self) -> Option<&nn::MatrixNetwork> {
+        match self {
+            BrainNetwork::Layered(genes)
+            | BrainNetwork::Pruned { genes, .. }
+            | BrainNetwork::Quantized { genes, .. } => Some(genes),
+            _ => None,
+        }
     }
 
-    pub(crate) fn from_chromosome(chromosome: ga::Chromosome, eye: &Eye) -> Brain {
-        Brain {
-            nn: nn::Network::from_weights(&Self::topology(eye), chromosome),
+    fn network_mut(&mut self) -> &mut dyn nn::Propagate {
+        match self {
+            BrainNetwork::Layered(network) => network,
+            BrainNetwork::Neat { network, .. } => network,
+            BrainNetwork::Ctrnn(network) => network,
+            BrainNetwork::Plastic(network) => network,
+            BrainNetwork::Pruned { network, .. } => network,
+            BrainNetwork::Quantized { network, .. } => network,
         }
     }
+}
 
-    fn topology(eye: &Eye) -> [nn::LayerTopology; 3] {
-        [
-            nn::LayerTopology {
-                neurons: eye.cells(),
-                activation: Activation::ReLU,
+impl Brain {
+    pub fn random(rng: &mut dyn RngCore, eye: &Eye, config: &Config) -> Brain {
+        let plastic = Self::is_plastic(config);
+
+        match config.brain_kind {
+            BrainKind::Layered if plastic => Brain {
+                nn: BrainNetwork::Plastic(nn::PlasticNetwork::random(
+                    rng,
+                    &Self::topology(eye, config),
+                )),
             },
-            nn::LayerTopology {
-                neurons: 2 * eye.cells(),
-                activation: Activation::ReLU,
+            BrainKind::Layered => Brain {
+                nn: BrainNetwork::layered(
+                    nn::MatrixNetwork::random(rng, &Self::topology(eye, config)),
+                    config,
+                ),
             },
-            nn::LayerTopology {
-                neurons: 2,
-                activation: Activation::Tanh,
+            BrainKind::Neat => Self::from_genome(neat::Genome::minimal(
+                rng,
+                Self::inputs(eye, config),
+                Self::outputs(config),
+            )),
+            BrainKind::Ctrnn => Brain {
+                nn: BrainNetwork::Ctrnn(nn::Ctrnn::random(
+                    rng,
+                    Self::inputs(eye, config),
+                    Self::ctrnn_neurons(config),
+                    Self::outputs(config),
+                    config.ctrnn_dt,
+                )),
             },
-        ]
+        }
+    }
+
+    pub(crate) fn propagate(&mut self, inputs: Vec<f32>) -> Vec<f32> {
+        self.nn.network_mut().propagate(inputs)
+    }
+
+    /// Inputs, hidden activations and outputs the brain would produce for
+    /// `inputs` on its next tick, without changing its memory. Recurrent and
+    /// plastic brains report what their current memory leads to, which is one
+    /// tick past the activations behind the latest actions.
+    pub(crate) fn trace(&self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        self.nn.clone().network_mut().trace(inputs)
+    }
+
+    /// Outputs of the brain for each of the `probes`, back to back, starting
+    /// from a clear memory so that brains can be compared
+    pub(crate) fn behaviour(&self, probes: &[Vec<f32>]) -> Vec<f32> {
+        let mut nn = self.nn.clone();
+        let network = nn.network_mut();
+
+        network.reset();
+        probes
+            .iter()
+            .flat_map(|probe| network.propagate(probe.clone()))
+            .collect()
+    }
+
+    /// Propagates every brain, with `inputs` holding their inputs back to back
+    /// and `outputs` filled with their outputs in the same way. Layered brains
+    /// go through `nn::MatrixNetwork::propagate_batch`, sharing the scratch
+    /// buffers instead of allocating.
+    pub(crate) fn propagate_batch<'a>(
+        brains: impl IntoIterator<Item = &'a mut Brain>,
+        inputs: &[f32],
+        inputs_per_brain: usize,
+        outputs: &mut Vec<f32>,
+        scratch: &mut nn::Scratch,
+    ) {
+        let mut brains: Vec<_> = brains.into_iter().collect();
+
+        let layered: Option<Vec<_>> = brains
+            .iter_mut()
+            .map(|brain| match &mut brain.nn {
+                BrainNetwork::Layered(network) => Some(network),
+                _ => None,
+            })
+            .collect();
+
+        if let Some(networks) = layered {
+            nn::MatrixNetwork::propagate_batch(networks, inputs, outputs, scratch);
+            return;
+        }
+
+        outputs.clear();
+
+        for (brain, inputs) in brains.into_iter().zip(inputs.chunks(inputs_per_brain)) {
+            outputs.extend(brain.propagate(inputs.to_vec()));
+        }
+    }
+
+    /// Adapts a plastic brain to its latest propagation; other brains stay fixed
+    pub(crate) fn learn(&mut self, modulation: f32) {
+        if let BrainNetwork::Plastic(network) = &mut self.nn {
+            network.learn(modulation);
+        }
+    }
+
+    /// Trains a layered brain to reproduce the targets of the samples, e.g.
+    /// recorded from a hand-crafted controller, returning the mean loss of
+    /// each epoch. Only layered brains made of dense layers, without
+    /// plasticity, can be trained; None is returned for any other brain.
+    /// Compressed brains are trained on their float weights, then compressed again.
+    pub(crate) fn imitate(
+        &mut self,
+        rng: &mut dyn RngCore,
+        samples: &[nn::Sample],
+        trainer: &nn::Trainer,
+        eye: &Eye,
+        config: &Config,
+    ) -> Option<Vec<f32>> {
+        let genes = self.nn.genes()?;
+
+        let topology = Self::topology(eye, config);
+        if topology
+            .iter()
+            .any(|layer| layer.kind != nn::LayerKind::Dense)
+        {
+            return None;
+        }
+
+        let mut network = nn::Network::from(genes);
+        let losses = trainer.train(rng, &mut network, samples);
+        self.nn = BrainNetwork::layered(nn::MatrixNetwork::from(&network), config);
+
+        Some(losses)
+    }
+
+    /// Weights of a layered or CTRNN brain, along with the learning rule of a
+    /// plastic one; NEAT brains aren't part of the chromosome
+    pub(crate) fn as_chromosome(&self) -> ga::Chromosome {
+        match &self.nn {
+            BrainNetwork::Layered(genes)
+            | BrainNetwork::Pruned { genes, .. }
+            | BrainNetwork::Quantized { genes, .. } => genes.weights().collect(),
+            BrainNetwork::Neat { .. } => ga::Chromosome::from_iter(Vec::new()),
+            BrainNetwork::Ctrnn(network) => network.weights().collect(),
+            BrainNetwork::Plastic(network) => network.weights().collect(),
+        }
+    }
+
+    pub(crate) fn from_chromosome(
+        kind: EncodedBrain,
+        chromosome: ga::Chromosome,
+        eye: &Eye,
+        config: &Config,
+    ) -> Brain {
+        let nn = match kind {
+            EncodedBrain::Plastic => BrainNetwork::Plastic(nn::PlasticNetwork::from_weights(
+                &Self::topology(eye, config),
+                chromosome,
+            )),
+            EncodedBrain::Layered => BrainNetwork::layered(
+                nn::MatrixNetwork::from_weights(&Self::topology(eye, config), chromosome),
+                config,
+            ),
+            EncodedBrain::Ctrnn => BrainNetwork::Ctrnn(nn::Ctrnn::from_weights(
+                Self::inputs(eye, config),
+                Self::ctrnn_neurons(config),
+                Self::outputs(config),
+                config.ctrnn_dt,
+                chromosome,
+            )),
+        };
+
+        Brain { nn }
+    }
+
+    /// Breeds NEAT brains, whose varying topology can't be carried by the
+    /// chromosome. Other brains are inherited through the chromosome instead,
+    /// so only their kind is returned for them.
+    pub(crate) fn crossover(
+        rng: &mut dyn RngCore,
+        parent_a: &Brain,
+        fitness_a: f32,
+        parent_b: &Brain,
+        fitness_b: f32,
+        innovations: &mut neat::InnovationHistory,
+        config: &Config,
+    ) -> Inheritance {
+        let genome = match (&parent_a.nn, &parent_b.nn) {
+            (
+                BrainNetwork::Neat {
+                    genome: genome_a, ..
+                },
+                BrainNetwork::Neat {
+                    genome: genome_b, ..
+                },
+            ) => neat::Genome::crossover(rng, genome_a, fitness_a, genome_b, fitness_b),
+            // A NEAT brain can only pass on its own genome to a mixed offspring
+            (BrainNetwork::Neat { genome, .. }, _) | (_, BrainNetwork::Neat { genome, .. }) => {
+                genome.clone()
+            }
+            (
+                BrainNetwork::Layered(_)
+                | BrainNetwork::Pruned { .. }
+                | BrainNetwork::Quantized { .. },
+                _,
+            ) => return Inheritance::Encoded(EncodedBrain::Layered),
+            (BrainNetwork::Plastic(_), _) => return Inheritance::Encoded(EncodedBrain::Plastic),
+            (BrainNetwork::Ctrnn(_), _) => return Inheritance::Encoded(EncodedBrain::Ctrnn),
+        };
+        let mut genome = genome;
+
+        neat::Mutation::new(
+            config.mutation_rate,
+            config.mutation_strength,
+            config.neat_add_node_rate,
+            config.neat_add_connection_rate,
+            config.neat_toggle_rate,
+            config.neat_activation_rate,
+            config.neat_activations.clone(),
+        )
+        .mutate(rng, &mut genome, innovations);
+
+        Inheritance::Bred(Self::from_genome(genome))
+    }
+
+    /// Innovation history agreeing with the initial NEAT brains
+    pub(crate) fn innovations(config: &Config) -> neat::InnovationHistory {
+        neat::InnovationHistory::new(
+            Self::inputs(&Eye::from_config(config), config),
+            Self::outputs(config),
+        )
+    }
+
+    /// Number of weights and biases in the network
+    pub(crate) fn parameters(&self) -> usize {
+        match &self.nn {
+            BrainNetwork::Layered(genes)
+            | BrainNetwork::Pruned { genes, .. }
+            | BrainNetwork::Quantized { genes, .. } => genes.weights().count(),
+            BrainNetwork::Neat { genome, .. } => genome.parameters(),
+            BrainNetwork::Ctrnn(network) => network.weights().count(),
+            BrainNetwork::Plastic(network) => network.weights().count(),
+        }
+    }
+
+    /// Number of genes needed to encode a brain, including biases
+    pub(crate) fn chromosome_length(config: &Config) -> usize {
+        let plastic = Self::is_plastic(config);
+
+        match config.brain_kind {
+            BrainKind::Layered if plastic => {
+                nn::PlasticNetwork::parameters(&Self::topology(&Eye::from_config(config), config))
+            }
+            BrainKind::Layered => {
+                nn::Network::parameters(&Self::topology(&Eye::from_config(config), config))
+            }
+            BrainKind::Neat => 0,
+            BrainKind::Ctrnn => nn::Ctrnn::parameters(
+                Self::inputs(&Eye::from_config(config), config),
+                Self::ctrnn_neurons(config),
+            ),
+        }
+    }
+
+    /// Thrust and torque, followed by a deposit amount per pheromone channel
+    /// and the value broadcast on each signal channel
+    pub(crate) fn outputs(config: &Config) -> usize {
+        2 + config.pheromone_channels + config.signal_channels
+    }
+
+    /// Eye cells, followed by a left and right antenna per pheromone channel,
+    /// the strength received on each signal channel and any proprioceptive senses
+    pub(crate) fn inputs(eye: &Eye, config: &Config) -> usize {
+        eye.cells()
+            + 2 * config.pheromone_channels
+            + config.signal_channels
+            + proprioceptive_inputs(config)
+    }
+
+    /// Inputs, followed by the hidden layers from the config and the outputs
+    fn topology(eye: &Eye, config: &Config) -> Vec<nn::LayerTopology> {
+        assert!(
+            config
+                .brain_hidden_layers
+                .iter()
+                .all(|layer| layer.neurons > 0),
+            "Brain hidden layers must have at least one neuron"
+        );
+        assert!(
+            matches!(
+                config.brain_output_activation,
+                nn::Activation::Tanh | nn::Activation::None
+            ),
+            "Brain outputs are read as signed thrust and torque, so the output activation must \
+             be Tanh or None, not {}",
+            config.brain_output_activation
+        );
+
+        let inputs = nn::LayerTopology {
+            // Activation of the input layer is unused
+            neurons: Self::inputs(eye, config),
+            activation: nn::Activation::None,
+            kind: nn::LayerKind::Dense,
+            initializer: nn::Initializer::default(),
+        };
+        let outputs = nn::LayerTopology {
+            neurons: Self::outputs(config),
+            activation: config.brain_output_activation,
+            kind: nn::LayerKind::Dense,
+            initializer: config.brain_output_initializer,
+        };
+
+        once(inputs)
+            .chain(config.brain_hidden_layers.iter().copied())
+            .chain(once(outputs))
+            .collect()
+    }
+
+    /// Whether layered brains learn during their lifetime
+    fn is_plastic(config: &Config) -> bool {
+        assert!(
+            !config.brain_plasticity || config.brain_kind == BrainKind::Layered,
+            "Plasticity is only supported by layered brains, not {} ones",
+            config.brain_kind
+        );
+
+        config.brain_plasticity
+    }
+
+    /// CTRNN outputs are read from its first neurons, so there must be enough of them
+    fn ctrnn_neurons(config: &Config) -> usize {
+        assert!(
+            config.ctrnn_neurons >= Self::outputs(config),
+            "CTRNN brains need at least {} neurons to drive every output, not {}",
+            Self::outputs(config),
+            config.ctrnn_neurons
+        );
+
+        config.ctrnn_neurons
+    }
+
+    fn from_genome(genome: neat::Genome) -> Brain {
+        Brain {
+            nn: BrainNetwork::Neat {
+                network: neat::Network::from_genome(&genome),
+                genome,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    mod crossover {
+        use super::*;
+
+        #[test]
+        fn test_neat() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let config = Config {
+                brain_kind: BrainKind::Neat,
+                neat_add_node_rate: 1.0,
+                ..Default::default()
+            };
+            let eye = Eye::from_config(&config);
+            let mut innovations = Brain::innovations(&config);
+            let parent_a = Brain::random(&mut rng, &eye, &config);
+            let parent_b = Brain::random(&mut rng, &eye, &config);
+
+            let child = Brain::crossover(
+                &mut rng,
+                &parent_a,
+                1.0,
+                &parent_b,
+                2.0,
+                &mut innovations,
+                &config,
+            );
+            let Inheritance::Bred(mut child) = child else {
+                panic!("NEAT brains are bred");
+            };
+
+            // The added node is a new bias, while the split connection is disabled
+            assert_eq!(child.parameters(), parent_a.parameters() + 2);
+            assert!(child.as_chromosome().is_empty());
+            assert_eq!(Brain::chromosome_length(&config), 0);
+            assert_eq!(
+                child
+                    .propagate(vec![0.5; Brain::inputs(&eye, &config)])
+                    .len(),
+                Brain::outputs(&config)
+            );
+        }
+
+        #[test]
+        fn test_layered() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let config = Config::default();
+            let eye = Eye::from_config(&config);
+            let mut innovations = Brain::innovations(&config);
+            let parent_a = Brain::random(&mut rng, &eye, &config);
+            let parent_b = Brain::random(&mut rng, &eye, &config);
+
+            assert!(matches!(
+                Brain::crossover(
+                    &mut rng,
+                    &parent_a,
+                    1.0,
+                    &parent_b,
+                    2.0,
+                    &mut innovations,
+                    &config,
+                ),
+                Inheritance::Encoded(EncodedBrain::Layered)
+            ));
+        }
+    }
+
+    mod propagate_batch {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let layered = Config::default();
+            let ctrnn = Config {
+                brain_kind: BrainKind::Ctrnn,
+                ..Default::default()
+            };
+            let eye = Eye::from_config(&layered);
+            let inputs_per_brain = Brain::inputs(&eye, &layered);
+            let mut brains = vec![
+                Brain::random(&mut rng, &eye, &layered),
+                Brain::random(&mut rng, &eye, &ctrnn),
+                Brain::random(&mut rng, &eye, &layered),
+            ];
+            let inputs: Vec<_> = (0..3 * inputs_per_brain)
+                .map(|n| n as f32 / 100.0)
+                .collect();
+
+            let expected: Vec<_> = brains
+                .clone()
+                .iter_mut()
+                .zip(inputs.chunks(inputs_per_brain))
+                .flat_map(|(brain, inputs)| brain.propagate(inputs.to_vec()))
+                .collect();
+
+            let mut outputs = Vec::new();
+            Brain::propagate_batch(
+                &mut brains,
+                &inputs,
+                inputs_per_brain,
+                &mut outputs,
+                &mut nn::Scratch::default(),
+            );
+
+            assert_eq!(outputs, expected);
+        }
+    }
+
+    mod trace {
+        use super::*;
+
+        #[test]
+        fn test() {
+            for brain_kind in [BrainKind::Layered, BrainKind::Neat, BrainKind::Ctrnn] {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let config = Config {
+                    brain_kind,
+                    ..Default::default()
+                };
+                let eye = Eye::from_config(&config);
+                let mut brain = Brain::random(&mut rng, &eye, &config);
+                let inputs = vec![0.5; Brain::inputs(&eye, &config)];
+
+                let trace = brain.trace(inputs.clone());
+                assert_eq!(trace.len(), 3);
+                assert_eq!(trace[0], inputs);
+                assert_eq!(trace[2], brain.propagate(inputs));
+            }
+        }
+    }
+
+    mod imitate {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let config = Config::default();
+            let eye = Eye::from_config(&config);
+            let mut brain = Brain::random(&mut rng, &eye, &config);
+
+            // Turns towards whichever half of the eye sees more food
+            let inputs = Brain::inputs(&eye, &config);
+            let samples: Vec<_> = (0..32)
+                .map(|n| {
+                    let inputs: Vec<_> = (0..inputs)
+                        .map(|i| ((n * 7 + i) % 5) as f32 / 5.0)
+                        .collect();
+                    let (left, right) = inputs[..eye.cells()].split_at(eye.cells() / 2);
+                    let torque = (left.iter().sum::<f32>() - right.iter().sum::<f32>()).tanh();
+
+                    let mut targets = vec![0.0; Brain::outputs(&config)];
+                    targets[0] = 0.5;
+                    targets[1] = torque;
+
+                    nn::Sample::new(inputs, targets)
+                })
+                .collect();
+            let trainer = nn::Trainer::new(
+                nn::Loss::MeanSquaredError,
+                nn::Optimizer::adam(0.01),
+                100,
+                8,
+            );
+
+            let losses = brain
+                .imitate(&mut rng, &samples, &trainer, &eye, &config)
+                .unwrap();
+            assert!(losses[losses.len() - 1] < losses[0] / 5.0);
+
+            let outputs = brain.propagate(samples[0].inputs.clone());
+            assert!((outputs[0] - 0.5).abs() < 0.1);
+        }
+
+        fn assert_unsupported(config: Config) {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let eye = Eye::from_config(&config);
+            let mut brain = Brain::random(&mut rng, &eye, &config);
+            let trainer =
+                nn::Trainer::new(nn::Loss::MeanSquaredError, nn::Optimizer::sgd(0.1), 1, 1);
+
+            assert!(brain
+                .imitate(&mut rng, &[], &trainer, &eye, &config)
+                .is_none());
+        }
+
+        #[test]
+        fn test_neat() {
+            assert_unsupported(Config {
+                brain_kind: BrainKind::Neat,
+                ..Default::default()
+            });
+        }
+
+        #[test]
+        fn test_plastic() {
+            assert_unsupported(Config {
+                brain_plasticity: true,
+                ..Default::default()
+            });
+        }
+
+        #[test]
+        fn test_recurrent() {
+            assert_unsupported(Config {
+                brain_hidden_layers: vec![nn::LayerTopology {
+                    neurons: 4,
+                    activation: nn::Activation::Tanh,
+                    kind: nn::LayerKind::Elman,
+                    initializer: nn::Initializer::Uniform,
+                }],
+                ..Default::default()
+            });
+        }
+    }
+
+    mod chromosome {
+        use super::*;
+
+        #[test]
+        fn test_ctrnn() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let config = Config {
+                brain_kind: BrainKind::Ctrnn,
+                ctrnn_neurons: 4,
+                ..Default::default()
+            };
+            let eye = Eye::from_config(&config);
+            let inputs = Brain::inputs(&eye, &config);
+            let brain = Brain::random(&mut rng, &eye, &config);
+
+            let chromosome = brain.as_chromosome();
+            assert_eq!(chromosome.len(), 4 * (2 + inputs + 4));
+            assert_eq!(chromosome.len(), Brain::chromosome_length(&config));
+
+            let mut child = Brain::from_chromosome(EncodedBrain::Ctrnn, chromosome, &eye, &config);
+            assert_eq!(child.parameters(), brain.parameters());
+            assert_eq!(
+                child.propagate(vec![0.5; inputs]).len(),
+                Brain::outputs(&config)
+            );
+        }
+
+        #[test]
+        fn test_plastic() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let config = Config {
+                brain_plasticity: true,
+                ..Default::default()
+            };
+            let eye = Eye::from_config(&config);
+            let inputs = vec![0.5; Brain::inputs(&eye, &config)];
+            let mut brain = Brain::random(&mut rng, &eye, &config);
+            let chromosome = brain.as_chromosome();
+
+            assert_eq!(
+                chromosome.len(),
+                nn::Network::parameters(&Brain::topology(&eye, &config))
+                    + nn::HebbianRule::PARAMETERS
+            );
+            assert_eq!(chromosome.len(), Brain::chromosome_length(&config));
+
+            // Learned weights change the behaviour, but aren't inherited
+            let before = brain.propagate(inputs.clone());
+            brain.learn(10.0);
+            assert_ne!(brain.propagate(inputs.clone()), before);
+            assert_eq!(brain.as_chromosome(), chromosome);
+
+            let mut child =
+                Brain::from_chromosome(EncodedBrain::Plastic, chromosome, &eye, &config);
+            assert_eq!(child.propagate(inputs), before);
+        }
+
+        #[test]
+        #[should_panic(expected = "Plasticity is only supported by layered brains, not neat ones")]
+        fn test_plastic_neat() {
+            let config = Config {
+                brain_kind: BrainKind::Neat,
+                brain_plasticity: true,
+                ..Default::default()
+            };
+
+            Brain::chromosome_length(&config);
+        }
+
+        #[test]
+        #[should_panic(expected = "CTRNN brains need at least 2 neurons")]
+        fn test_too_few_ctrnn_neurons() {
+            let config = Config {
+                brain_kind: BrainKind::Ctrnn,
+                ctrnn_neurons: 1,
+                ..Default::default()
+            };
+
+            Brain::chromosome_length(&config);
+        }
+    }
+
+    mod compression {
+        use super::*;
+        use approx::assert_relative_eq;
+        use test_case::test_case;
+
+        #[test_case(BrainCompression::Pruned, 0.0, 1e-6)]
+        #[test_case(BrainCompression::Pruned, 0.05, 0.2)]
+        #[test_case(BrainCompression::Quantized, 0.0, 0.05)]
+        fn test(brain_compression: BrainCompression, brain_prune_threshold: f32, epsilon: f32) {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let config = Config {
+                brain_compression,
+                brain_prune_threshold,
+                ..Default::default()
+            };
+            let eye = Eye::from_config(&config);
+            let inputs = vec![0.5; Brain::inputs(&eye, &config)];
+            let mut brain = Brain::random(&mut rng, &eye, &Config::default());
+            let chromosome = brain.as_chromosome();
+
+            // The chromosome keeps the float weights, which the compressed brain approximates
+            let mut compressed =
+                Brain::from_chromosome(EncodedBrain::Layered, chromosome.clone(), &eye, &config);
+            assert_eq!(compressed.as_chromosome(), chromosome);
+            assert_eq!(compressed.parameters(), brain.parameters());
+            let actual = compressed.propagate(inputs.clone());
+            let expected = brain.propagate(inputs);
+            assert_relative_eq!(actual.as_slice(), expected.as_slice(), epsilon = epsilon);
+        }
+    }
+
+    mod topology {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let config = Config {
+                eye_cells: 5,
+                pheromone_channels: 1,
+                signal_channels: 2,
+                sense_energy: true,
+                sense_speed: false,
+                sense_age: false,
+                sense_walls: false,
+                sense_clock: false,
+                brain_hidden_layers: vec![
+                    nn::LayerTopology {
+                        neurons: 8,
+                        activation: nn::Activation::ReLU,
+                        kind: nn::LayerKind::Dense,
+                        initializer: nn::Initializer::Uniform,
+                    },
+                    nn::LayerTopology {
+                        neurons: 4,
+                        activation: nn::Activation::Sigmoid,
+                        kind: nn::LayerKind::Gru,
+                        initializer: nn::Initializer::Uniform,
+                    },
+                ],
+                brain_output_activation: nn::Activation::None,
+                brain_output_initializer: nn::Initializer::He,
+                ..Default::default()
+            };
+            let topology = Brain::topology(&Eye::from_config(&config), &config);
+
+            let neurons: Vec<_> = topology.iter().map(|layer| layer.neurons).collect();
+            assert_eq!(neurons, vec![5 + 2 + 2 + 1, 8, 4, 2 + 1 + 2]);
+            assert_eq!(topology[2].activation, nn::Activation::Sigmoid);
+            assert_eq!(topology[2].kind, nn::LayerKind::Gru);
+            assert_eq!(topology[3].activation, nn::Activation::None);
+            assert_eq!(topology[3].initializer, nn::Initializer::He);
+            assert_eq!(
+                Brain::chromosome_length(&config),
+                (10 + 1) * 8 + 3 * 4 * (8 + 4 + 1) + (4 + 1) * 5
+            );
+        }
+
+        #[test]
+        fn test_no_hidden_layers() {
+            let config = Config {
+                brain_hidden_layers: vec![],
+                ..Default::default()
+            };
+            let topology = Brain::topology(&Eye::from_config(&config), &config);
+
+            assert_eq!(topology.len(), 2);
+        }
+
+        #[test]
+        #[should_panic(expected = "output activation must be Tanh or None, not softmax")]
+        fn test_unsigned_output_activation() {
+            let config = Config {
+                brain_output_activation: nn::Activation::Softmax,
+                ..Default::default()
+            };
+
+            Brain::topology(&Eye::from_config(&config), &config);
+        }
+
+        #[test]
+        #[should_panic(expected = "at least one neuron")]
+        fn test_empty_hidden_layer() {
+            let config = Config {
+                brain_hidden_layers: vec![nn::LayerTopology {
+                    neurons: 0,
+                    activation: nn::Activation::ReLU,
+                    kind: nn::LayerKind::Dense,
+                    initializer: nn::Initializer::Uniform,
+                }],
+                ..Default::default()
+            };
+
+            Brain::topology(&Eye::from_config(&config), &config);
+        }
     }
 }
@@ -0,0 +1,174 @@
+use crate::*;
+
+/// Pushes overlapping creatures apart in proportion to their masses and exchanges
+/// momentum along the contact normal, with `collision_elasticity` ranging from
+/// perfectly inelastic (0) to perfectly elastic (1)
+pub(crate) fn resolve_collisions(creatures: &mut [Creature], config: &Config) {
+    let max_size = creatures
+        .iter()
+        .map(|creature| creature.body.size)
+        .fold(0.0, f32::max);
+    if max_size <= 0.0 {
+        return;
+    }
+
+    let positions: Vec<_> = creatures.iter().map(|creature| creature.position).collect();
+    let grid = SpatialGrid::new(&positions, max_size);
+
+    for idx in 0..creatures.len() {
+        let neighbours: Vec<_> = grid
+            .query(positions[idx], max_size)
+            .filter(|other_idx| *other_idx > idx)
+            .collect();
+
+        for other_idx in neighbours {
+            let (left, right) = creatures.split_at_mut(other_idx);
+            collide(&mut left[idx], &mut right[0], config);
+        }
+    }
+}
+
+fn collide(a: &mut Creature, b: &mut Creature, config: &Config) {
+    let offset = b.position - a.position;
+    let distance = offset.norm();
+    let overlap = (a.body.size + b.body.size) / 2.0 - distance;
+
+    if overlap <= 0.0 {
+        return;
+    }
+
+    let normal = if distance > 0.0 {
+        offset / distance
+    } else {
+        na::Vector2::x()
+    };
+
+    let mass_a = a.body.mass(config);
+    let mass_b = b.body.mass(config);
+    let total_mass = mass_a + mass_b;
+
+    // Separate the pair, moving the lighter creature further
+    a.position -= normal * overlap * mass_b / total_mass;
+    b.position += normal * overlap * mass_a / total_mass;
+
+    // Only exchange momentum if the pair is approaching
+    let approach = (b.body.velocity - a.body.velocity).dot(&normal);
+    if approach < 0.0 {
+        let impulse =
+            -(1.0 + config.collision_elasticity) * approach / (1.0 / mass_a + 1.0 / mass_b);
+        a.body.velocity -= normal * impulse / mass_a;
+        b.body.velocity += normal * impulse / mass_b;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn config(elasticity: f32) -> Config {
+        Config {
+            creature_size: 0.01,
+            collision_elasticity: elasticity,
+            ..Default::default()
+        }
+    }
+
+    fn creature(
+        rng: &mut dyn RngCore,
+        config: &Config,
+        x: f32,
+        size: f32,
+        velocity: f32,
+    ) -> Creature {
+        let mut creature = Creature::random(rng, config);
+        creature.position = na::Point2::new(x, 0.5);
+        creature.body.size = size;
+        creature.body.velocity = na::Vector2::new(velocity, 0.0);
+        creature
+    }
+
+    mod resolve_collisions {
+        use super::*;
+
+        #[test]
+        fn test_separates_overlapping_creatures() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let config = config(0.0);
+            let mut creatures = vec![
+                creature(&mut rng, &config, 0.5, 0.01, 0.0),
+                creature(&mut rng, &config, 0.506, 0.01, 0.0),
+                creature(&mut rng, &config, 0.8, 0.01, 0.0),
+            ];
+
+            resolve_collisions(&mut creatures, &config);
+
+            assert_relative_eq!(creatures[0].position.x, 0.498, epsilon = 1e-6);
+            assert_relative_eq!(creatures[1].position.x, 0.508, epsilon = 1e-6);
+            assert_relative_eq!(creatures[2].position.x, 0.8);
+        }
+
+        #[test]
+        fn test_heavier_creatures_move_less() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let config = config(0.0);
+            let mut creatures = vec![
+                creature(&mut rng, &config, 0.5, 0.01, 0.0),
+                creature(&mut rng, &config, 0.51, 0.02, 0.0),
+            ];
+
+            resolve_collisions(&mut creatures, &config);
+
+            // Overlap of 0.005 is split 4:1 by mass
+            assert_relative_eq!(creatures[0].position.x, 0.496, epsilon = 1e-6);
+            assert_relative_eq!(creatures[1].position.x, 0.511, epsilon = 1e-6);
+        }
+
+        #[test]
+        fn test_elastic_collision_swaps_velocities() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let config = config(1.0);
+            let mut creatures = vec![
+                creature(&mut rng, &config, 0.5, 0.01, 0.002),
+                creature(&mut rng, &config, 0.509, 0.01, -0.001),
+            ];
+
+            resolve_collisions(&mut creatures, &config);
+
+            assert_relative_eq!(creatures[0].body.velocity.x, -0.001, epsilon = 1e-7);
+            assert_relative_eq!(creatures[1].body.velocity.x, 0.002, epsilon = 1e-7);
+        }
+
+        #[test]
+        fn test_inelastic_collision_shares_velocity() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let config = config(0.0);
+            let mut creatures = vec![
+                creature(&mut rng, &config, 0.5, 0.01, 0.002),
+                creature(&mut rng, &config, 0.509, 0.01, -0.001),
+            ];
+
+            resolve_collisions(&mut creatures, &config);
+
+            assert_relative_eq!(creatures[0].body.velocity.x, 0.0005, epsilon = 1e-7);
+            assert_relative_eq!(creatures[1].body.velocity.x, 0.0005, epsilon = 1e-7);
+        }
+
+        #[test]
+        fn test_separating_creatures_keep_velocity() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let config = config(1.0);
+            let mut creatures = vec![
+                creature(&mut rng, &config, 0.5, 0.01, -0.001),
+                creature(&mut rng, &config, 0.509, 0.01, 0.001),
+            ];
+
+            resolve_collisions(&mut creatures, &config);
+
+            assert_relative_eq!(creatures[0].body.velocity.x, -0.001);
+            assert_relative_eq!(creatures[1].body.velocity.x, 0.001);
+        }
+    }
+}
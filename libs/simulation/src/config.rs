@@ -1,5 +1,7 @@
 use std::f32::consts::{FRAC_PI_4, FRAC_PI_8, PI};
 
+use crate::*;
+
 pub struct Config {
     pub population_count: usize, // Number of individuals in the population
     pub target_population: usize,
@@ -8,21 +10,81 @@ pub struct Config {
     pub food_size: f32,              // Size of the food
     pub starting_energy: f32,        // Starting energy of the creatures
     pub food_energy: f32,            // Energy gained from each food
-    pub energy_loss_factor: f32,     // Energy lost each tick * Creature speed
     pub reproduction_cost: f32,      // Energy cost to reproduce
     pub reproduction_threshold: f32, // Threshold for allowing reproduction
-    pub speed_min: f32,              // Minimum Creature speed
     pub speed_max: f32,              // Maximum Creature speed
-    pub speed_accel: f32,            // Change in speed per update
-    pub rotation_accel: f32,         // Change in rotation per update
+    pub angular_speed_max: f32,      // Maximum Creature angular speed
+    pub max_thrust: f32,             // Forward force at full Brain output
+    pub max_torque: f32,             // Turning force at full Brain output
+    pub linear_drag: f32,            // Fraction of velocity lost per unit time
+    pub angular_drag: f32,           // Fraction of angular velocity lost per unit time
+    pub dt: f32,                     // Physics timestep per update
     pub mutation_rate: f32,          // Probability of mutation [0, 1]
     pub mutation_strength: f32,      // Multiplied factor of mutation
-    pub fov_range: f32,
-    pub fov_angle: f32,
+    pub fov_range: f32,              // Initial FOV range, also the reference for vision cost
+    pub fov_angle: f32,              // Initial FOV angle, also the reference for vision cost
+    pub fov_range_min: f32,          // Lower bound for evolved FOV range
+    pub fov_range_max: f32,          // Upper bound for evolved FOV range
+    pub fov_angle_min: f32,          // Lower bound for evolved FOV angle
+    pub fov_angle_max: f32,          // Upper bound for evolved FOV angle
     pub eye_cells: usize,
+    pub terrain_resolution: usize,     // Terrain grid cells per axis
+    pub terrain_variation: f32,        // Strength of terrain features [0, 1]
+    pub terrain_temperature_cost: f32, // Extra metabolic cost at extreme temperatures
+    pub pheromone_channels: usize,     // Number of pheromone channels, 0 disables pheromones
+    pub pheromone_resolution: usize,   // Pheromone grid cells per axis
+    pub pheromone_diffusion: f32,      // Fraction of each cell spread to neighbours per tick
+    pub pheromone_evaporation: f32,    // Fraction of each cell lost per tick
+    pub pheromone_deposit: f32,        // Amount deposited per tick at full brain output
+    pub pheromone_antenna: f32,        // Distance from a Creature to its smell sensors
+    pub signal_channels: usize,        // Number of signal channels, 0 disables signalling
+    pub signal_range: f32,             // Distance at which signals fade out completely
+    pub signal_color: bool,            // Drive Creature color from the first signal channel
+    pub max_lifespan: usize,           // Ticks before a Creature dies of old age, 0 for immortality
+    pub senescence_onset: f32,         // Fraction of the lifespan before senescence, 1 for none
+    pub senescence_energy_factor: f32, // Extra metabolic cost at full senescence
+    pub senescence_speed_factor: f32,  // Fraction of speed lost at full senescence [0, 1]
+    pub basal_cost: f32,               // Energy lost each tick * mass^0.75
+    pub movement_cost: f32,            // Energy lost each tick * mass * (speed / speed_max)^2
+    pub rotation_cost: f32,            // Energy lost each tick * (torque / max_torque)^2
+    pub brain_cost: f32,               // Energy lost each tick * brain parameter count
+    pub vision_cost: f32,              // Energy lost each tick * FOV area relative to default
+    pub energy_capacity: f32,          // Maximum energy stored by a Creature of default size
+    pub creature_collisions: bool,     // Prevent creatures from overlapping each other
+    pub collision_elasticity: f32,     // Restitution of Creature collisions [0, 1]
+    pub sense_energy: bool,            // Brain input for energy relative to capacity
+    pub sense_speed: bool,             // Brain input for speed relative to speed_max
+    pub sense_age: bool,               // Brain input for age relative to max_lifespan
+    pub sense_walls: bool,             // Brain inputs for wall proximity ahead and nearest
+    pub sense_clock: bool,             // Brain inputs for a constant bias and an oscillator
+    pub clock_period: usize,           // Ticks per oscillator cycle
+    pub brain_hidden_layers: Vec<nn::LayerTopology>, // Size, activation, kind and initializer of each hidden layer
+    pub brain_output_activation: nn::Activation,     // Must produce signed values, e.g. Tanh
+    pub brain_output_initializer: nn::Initializer,   // Initial weights of the output layer
+    pub brain_kind: BrainKind, // Layered brains, or topologies evolved by NEAT
+    pub neat_add_node_rate: f32, // Probability of a NEAT brain gaining a node
+    pub neat_add_connection_rate: f32, // Probability of a NEAT brain gaining a connection
+    pub neat_toggle_rate: f32, // Probability of a NEAT connection being toggled
+    pub neat_activation_rate: f32, // Probability of a hidden NEAT node switching activation
+    pub neat_activations: Vec<nn::Activation>, // Activations hidden NEAT nodes can switch to
+    pub ctrnn_neurons: usize,  // Neurons of a CTRNN brain, the first of which drive the outputs
+    pub ctrnn_dt: f32,         // Integration timestep of a CTRNN brain, per tick
+    pub brain_plasticity: bool, // Layered brains adapt during life through an evolved Hebbian rule
+    pub plasticity_baseline: f32, // Modulation of the Hebbian rule on every tick
+    pub plasticity_reward: f32, // Extra modulation of the Hebbian rule per food eaten
+    pub brain_compression: BrainCompression, // How non-plastic layered brains run
+    pub brain_prune_threshold: f32, // Magnitude below which pruned brains drop a weight
+    pub diversity_interval: usize, // Steps between diversity measurements, 0 disables them
+    pub diversity_sample: usize, // Creatures compared when measuring diversity
+    pub diversity_probes: usize, // Random brain inputs on which behaviours are compared
+    pub novelty_weight: f32,   // Share of novelty in the chance to reproduce, 0 disables it
+    pub novelty_neighbours: usize, // Nearest positions averaged into a Creature's novelty
+    pub novelty_threshold: f32, // Novelty past which a position is archived
+    pub novelty_archive: usize, // Most positions kept in the archive
 }
 
 impl Config {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         population_count: usize,
         target_population: usize,
@@ -31,18 +93,77 @@ impl Config {
         food_size: f32,
         starting_energy: f32,
         food_energy: f32,
-        energy_loss_factor: f32,
         reproduction_cost: f32,
         reproduction_threshold: f32,
-        speed_min: f32,
         speed_max: f32,
-        speed_accel: f32,
-        rotation_accel: f32,
+        angular_speed_max: f32,
+        max_thrust: f32,
+        max_torque: f32,
+        linear_drag: f32,
+        angular_drag: f32,
+        dt: f32,
         mutation_rate: f32,
         mutation_strength: f32,
         fov_range: f32,
         fov_angle: f32,
+        fov_range_min: f32,
+        fov_range_max: f32,
+        fov_angle_min: f32,
+        fov_angle_max: f32,
         eye_cells: usize,
+        terrain_resolution: usize,
+        terrain_variation: f32,
+        terrain_temperature_cost: f32,
+        pheromone_channels: usize,
+        pheromone_resolution: usize,
+        pheromone_diffusion: f32,
+        pheromone_evaporation: f32,
+        pheromone_deposit: f32,
+        pheromone_antenna: f32,
+        signal_channels: usize,
+        signal_range: f32,
+        signal_color: bool,
+        max_lifespan: usize,
+        senescence_onset: f32,
+        senescence_energy_factor: f32,
+        senescence_speed_factor: f32,
+        basal_cost: f32,
+        movement_cost: f32,
+        rotation_cost: f32,
+        brain_cost: f32,
+        vision_cost: f32,
+        energy_capacity: f32,
+        creature_collisions: bool,
+        collision_elasticity: f32,
+        sense_energy: bool,
+        sense_speed: bool,
+        sense_age: bool,
+        sense_walls: bool,
+        sense_clock: bool,
+        clock_period: usize,
+        brain_hidden_layers: Vec<nn::LayerTopology>,
+        brain_output_activation: nn::Activation,
+        brain_output_initializer: nn::Initializer,
+        brain_kind: BrainKind,
+        neat_add_node_rate: f32,
+        neat_add_connection_rate: f32,
+        neat_toggle_rate: f32,
+        neat_activation_rate: f32,
+        neat_activations: Vec<nn::Activation>,
+        ctrnn_neurons: usize,
+        ctrnn_dt: f32,
+        brain_plasticity: bool,
+        plasticity_baseline: f32,
+        plasticity_reward: f32,
+        brain_compression: BrainCompression,
+        brain_prune_threshold: f32,
+        diversity_interval: usize,
+        diversity_sample: usize,
+        diversity_probes: usize,
+        novelty_weight: f32,
+        novelty_neighbours: usize,
+        novelty_threshold: f32,
+        novelty_archive: usize,
     ) -> Config {
         Config {
             population_count,
@@ -52,19 +173,148 @@ impl Config {
             food_size,
             starting_energy,
             food_energy,
-            energy_loss_factor,
             reproduction_cost,
             reproduction_threshold,
-            speed_min,
             speed_max,
-            speed_accel,
-            rotation_accel,
+            angular_speed_max,
+            max_thrust,
+            max_torque,
+            linear_drag,
+            angular_drag,
+            dt,
             mutation_rate,
             mutation_strength,
             fov_range,
             fov_angle,
+            fov_range_min,
+            fov_range_max,
+            fov_angle_min,
+            fov_angle_max,
             eye_cells,
+            terrain_resolution,
+            terrain_variation,
+            terrain_temperature_cost,
+            pheromone_channels,
+            pheromone_resolution,
+            pheromone_diffusion,
+            pheromone_evaporation,
+            pheromone_deposit,
+            pheromone_antenna,
+            signal_channels,
+            signal_range,
+            signal_color,
+            max_lifespan,
+            senescence_onset,
+            senescence_energy_factor,
+            senescence_speed_factor,
+            basal_cost,
+            movement_cost,
+            rotation_cost,
+            brain_cost,
+            vision_cost,
+            energy_capacity,
+            creature_collisions,
+            collision_elasticity,
+            sense_energy,
+            sense_speed,
+            sense_age,
+            sense_walls,
+            sense_clock,
+            clock_period,
+            brain_hidden_layers,
+            brain_output_activation,
+            brain_output_initializer,
+            brain_kind,
+            neat_add_node_rate,
+            neat_add_connection_rate,
+            neat_toggle_rate,
+            neat_activation_rate,
+            neat_activations,
+            ctrnn_neurons,
+            ctrnn_dt,
+            brain_plasticity,
+            plasticity_baseline,
+            plasticity_reward,
+            brain_compression,
+            brain_prune_threshold,
+            diversity_interval,
+            diversity_sample,
+            diversity_probes,
+            novelty_weight,
+            novelty_neighbours,
+            novelty_threshold,
+            novelty_archive,
+        }
+    }
+
+    /// Checks the settings that would otherwise break the simulation part
+    /// way through, e.g. brain outputs that can't drive a Creature
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("pheromone_diffusion", self.pheromone_diffusion),
+            ("pheromone_evaporation", self.pheromone_evaporation),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("{} must be within [0, 1], not {}", name, value));
+            }
+        }
+
+        if self
+            .brain_hidden_layers
+            .iter()
+            .any(|layer| layer.neurons == 0)
+        {
+            return Err("Brain hidden layers must have at least one neuron".to_string());
+        }
+
+        if !matches!(
+            self.brain_output_activation,
+            nn::Activation::Tanh | nn::Activation::None
+        ) {
+            return Err(format!(
+                "Brain outputs are read as signed thrust and torque, so the output activation \
+                 must be tanh or none, not {}",
+                self.brain_output_activation
+            ));
+        }
+
+        if self.brain_plasticity && self.brain_kind != BrainKind::Layered {
+            return Err(format!(
+                "Plasticity is only supported by layered brains, not {} ones",
+                self.brain_kind
+            ));
+        }
+
+        if self.brain_compression != BrainCompression::None
+            && self
+                .brain_hidden_layers
+                .iter()
+                .any(|layer| layer.kind == nn::LayerKind::Gru)
+        {
+            return Err(format!(
+                "{} brains don't support gru layers",
+                self.brain_compression
+            ));
+        }
+
+        if self.brain_kind == BrainKind::Ctrnn {
+            if self.ctrnn_neurons < Brain::outputs(self) {
+                return Err(format!(
+                    "CTRNN brains need at least {} neurons to drive every output, not {}",
+                    Brain::outputs(self),
+                    self.ctrnn_neurons
+                ));
+            }
+
+            if self.ctrnn_dt <= 0.0 {
+                return Err(format!(
+                    "CTRNN timestep must be positive, not {}",
+                    self.ctrnn_dt
+                ));
+            }
         }
+
+        Ok(())
     }
 }
 
@@ -78,18 +328,124 @@ impl Default for Config {
             food_size: 0.005,
             starting_energy: 100.0,
             food_energy: 75.0,
-            energy_loss_factor: 5.0,
             reproduction_cost: 50.0,
             reproduction_threshold: 100.0,
-            speed_min: 0.0001,
             speed_max: 0.0025,
-            speed_accel: 0.2,
-            rotation_accel: FRAC_PI_8 / 2.0,
+            angular_speed_max: FRAC_PI_8,
+            max_thrust: 0.00025,
+            max_torque: 0.05,
+            linear_drag: 0.1,
+            angular_drag: 0.5,
+            dt: 1.0,
             mutation_rate: 0.15,
             mutation_strength: 0.3,
             fov_range: 0.25,
             fov_angle: PI + FRAC_PI_4,
+            fov_range_min: 0.05,
+            fov_range_max: 0.5,
+            fov_angle_min: FRAC_PI_4,
+            fov_angle_max: 2.0 * PI,
             eye_cells: 9,
+            terrain_resolution: 32,
+            terrain_variation: 0.0,
+            terrain_temperature_cost: 0.5,
+            pheromone_channels: 0,
+            pheromone_resolution: 64,
+            pheromone_diffusion: 0.1,
+            pheromone_evaporation: 0.02,
+            pheromone_deposit: 0.1,
+            pheromone_antenna: 0.02,
+            signal_channels: 0,
+            signal_range: 0.1,
+            signal_color: false,
+            max_lifespan: 0,
+            senescence_onset: 0.6,
+            senescence_energy_factor: 1.0,
+            senescence_speed_factor: 0.5,
+            basal_cost: 0.02,
+            movement_cost: 0.05,
+            rotation_cost: 0.05,
+            brain_cost: 0.0001,
+            vision_cost: 0.01,
+            energy_capacity: 300.0,
+            creature_collisions: false,
+            collision_elasticity: 0.5,
+            sense_energy: false,
+            sense_speed: false,
+            sense_age: false,
+            sense_walls: false,
+            sense_clock: false,
+            clock_period: 100,
+            brain_hidden_layers: vec![nn::LayerTopology {
+                neurons: 18,
+                activation: nn::Activation::ReLU,
+                kind: nn::LayerKind::Dense,
+                initializer: nn::Initializer::Uniform,
+            }],
+            brain_output_activation: nn::Activation::Tanh,
+            brain_output_initializer: nn::Initializer::Uniform,
+            brain_kind: BrainKind::Layered,
+            neat_add_node_rate: 0.03,
+            neat_add_connection_rate: 0.05,
+            neat_toggle_rate: 0.01,
+            neat_activation_rate: 0.02,
+            neat_activations: vec![
+                nn::Activation::Tanh,
+                nn::Activation::Sine,
+                nn::Activation::Gaussian,
+                nn::Activation::PReLU(0.25),
+            ],
+            ctrnn_neurons: 12,
+            ctrnn_dt: 0.2,
+            brain_plasticity: false,
+            plasticity_baseline: 0.0,
+            plasticity_reward: 1.0,
+            brain_compression: BrainCompression::None,
+            brain_prune_threshold: 0.05,
+            diversity_interval: 100,
+            diversity_sample: 30,
+            diversity_probes: 8,
+            novelty_weight: 0.0,
+            novelty_neighbours: 10,
+            novelty_threshold: 0.1,
+            novelty_archive: 200,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod validate {
+        use super::*;
+        use test_case::test_case;
+
+        fn hidden_layer(neurons: usize, kind: nn::LayerKind) -> Vec<nn::LayerTopology> {
+            vec![nn::LayerTopology {
+                neurons,
+                activation: nn::Activation::Tanh,
+                kind,
+                initializer: nn::Initializer::Uniform,
+            }]
+        }
+
+        #[test]
+        fn test_default() {
+            assert_eq!(Config::default().validate(), Ok(()));
+        }
+
+        #[test_case(Config { pheromone_diffusion: 1.5, ..Default::default() }, "pheromone_diffusion")]
+        #[test_case(Config { pheromone_evaporation: -0.1, ..Default::default() }, "pheromone_evaporation")]
+        #[test_case(Config { brain_hidden_layers: hidden_layer(0, nn::LayerKind::Dense), ..Default::default() }, "at least one neuron")]
+        #[test_case(Config { brain_output_activation: nn::Activation::Sigmoid, ..Default::default() }, "not sigmoid")]
+        #[test_case(Config { brain_kind: BrainKind::Neat, brain_plasticity: true, ..Default::default() }, "not neat ones")]
+        #[test_case(Config { brain_compression: BrainCompression::Quantized, brain_hidden_layers: hidden_layer(4, nn::LayerKind::Gru), ..Default::default() }, "gru")]
+        #[test_case(Config { brain_kind: BrainKind::Ctrnn, ctrnn_neurons: 1, ..Default::default() }, "at least 2 neurons")]
+        #[test_case(Config { brain_kind: BrainKind::Ctrnn, ctrnn_dt: 0.0, ..Default::default() }, "timestep")]
+        fn test_invalid(config: Config, expected: &str) {
+            let err = config.validate().unwrap_err();
+            assert!(err.contains(expected), "{}", err);
         }
     }
 }
@@ -7,52 +7,67 @@ pub struct Creature {
     pub(crate) brain: Brain,
     pub(crate) body: Body,
     pub(crate) satiation: usize,
+    pub(crate) meals: usize, // Food eaten since the brain last learned
     pub(crate) generation: usize,
+    pub(crate) age: usize,        // Ticks since birth
+    pub(crate) signal: Vec<f32>,  // Values broadcast to nearby creatures
+    pub(crate) tint: Option<f32>, // Signal-driven color overriding the Body color
 }
 
 impl Creature {
     pub fn random(rng: &mut dyn RngCore, config: &Config) -> Creature {
-        let eye = Eye::new(config.fov_range, config.fov_angle, config.eye_cells);
-        let brain = Brain::random(rng, &eye);
+        let eye = Eye::from_config(config);
+        let brain = Brain::random(rng, &eye, config);
         let body = Body::random(rng, config);
 
-        Creature::new(eye, brain, body, rng)
+        Creature::new(eye, brain, body, rng, config)
     }
 
-    fn new(eye: Eye, brain: Brain, body: Body, rng: &mut dyn RngCore) -> Creature {
+    fn new(eye: Eye, brain: Brain, body: Body, rng: &mut dyn RngCore, config: &Config) -> Creature {
         Creature {
             position: rng.gen(),
             eye,
             brain,
             body,
             satiation: 0,
+            meals: 0,
             generation: 0,
+            age: 0,
+            signal: vec![0.0; config.signal_channels],
+            tint: None,
         }
     }
 
-    pub(crate) fn as_chromosome(&self) -> ga::Chromosome {
-        self.brain
-            .as_chromosome()
-            .into_iter()
-            .chain(self.body.as_chromosome().into_iter())
-            .collect()
+    pub(crate) fn as_chromosome(&self, config: &Config) -> ga::Chromosome {
+        GenomeLayout::new(config).encode(&[
+            ("brain", self.brain.as_chromosome().into_iter().collect()),
+            ("body", self.body.traits()),
+            ("eye", self.eye.traits()),
+        ])
     }
 
+    /// Decodes a Creature from its chromosome, unless it already has a `brain`
     pub(crate) fn from_chromosome(
         chromosome: ga::Chromosome,
+        brain: Inheritance,
         rng: &mut dyn RngCore,
         config: &Config,
     ) -> Creature {
-        let brain_chromosome_length = (config.eye_cells * 2 * config.eye_cells) // Input Layer
-            + (2 * config.eye_cells * 2) // Hidden Layer
-            + (config.eye_cells * 2 + 2); // Biases
-        let [brain_chromosome, body_chromosome] = chromosome.split_at(brain_chromosome_length);
+        let genome = GenomeLayout::new(config).decode(&chromosome);
 
-        let eye = Eye::new(config.fov_range, config.fov_angle, config.eye_cells);
-        let brain = Brain::from_chromosome(brain_chromosome, &eye);
-        let body = Body::from_chromosome(body_chromosome, rng, config);
+        let eye = Eye::from_traits(genome.segment("eye"), config);
+        let brain = match brain {
+            Inheritance::Bred(brain) => brain,
+            Inheritance::Encoded(kind) => Brain::from_chromosome(
+                kind,
+                genome.segment("brain").iter().copied().collect(),
+                &eye,
+                config,
+            ),
+        };
+        let body = Body::from_traits(genome.segment("body"), rng, config);
 
-        Creature::new(eye, brain, body, rng)
+        Creature::new(eye, brain, body, rng, config)
     }
 
     pub fn position(&self) -> na::Point2<f32> {
@@ -63,10 +78,36 @@ impl Creature {
         self.body.rotation
     }
 
+    pub fn velocity(&self) -> na::Vector2<f32> {
+        self.body.velocity
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.body.speed()
+    }
+
     pub fn generation(&self) -> usize {
         self.generation
     }
 
+    pub fn age(&self) -> usize {
+        self.age
+    }
+
+    /// Degree of age-related decline in [0, 1], rising quadratically from
+    /// `senescence_onset` of the maximum lifespan until death by old age.
+    /// An onset of 1 or more means creatures never decline.
+    pub fn senescence(&self, config: &Config) -> f32 {
+        if config.max_lifespan == 0 || config.senescence_onset >= 1.0 {
+            return 0.0;
+        }
+
+        let life = self.age as f32 / config.max_lifespan as f32;
+        let decline = (life - config.senescence_onset) / (1.0 - config.senescence_onset);
+
+        decline.clamp(0.0, 1.0).powi(2)
+    }
+
     pub fn fitness(&self) -> f32 {
         if self.body.energy <= 0.0 {
             0.0
@@ -79,11 +120,86 @@ impl Creature {
         self.body.energy
     }
 
+    pub fn energy_capacity(&self, config: &Config) -> f32 {
+        self.body.energy_capacity(config)
+    }
+
+    pub fn energy_report(&self) -> &EnergyReport {
+        &self.body.energy_report
+    }
+
+    pub fn eye(&self) -> &Eye {
+        &self.eye
+    }
+
     pub fn size(&self) -> f32 {
         self.body.size
     }
 
     pub fn color(&self) -> f32 {
-        self.body.color
+        self.tint.unwrap_or(self.body.color)
+    }
+
+    pub fn signal(&self) -> &[f32] {
+        &self.signal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    mod senescence {
+        use super::*;
+        use approx::assert_relative_eq;
+        use test_case::test_case;
+
+        #[test_case(0, 0.0)]
+        #[test_case(500, 0.0)]
+        #[test_case(750, 0.25)]
+        #[test_case(1000, 1.0)]
+        #[test_case(2000, 1.0)]
+        fn test(age: usize, expected: f32) {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let config = Config {
+                max_lifespan: 1000,
+                senescence_onset: 0.5,
+                ..Default::default()
+            };
+            let mut creature = Creature::random(&mut rng, &config);
+            creature.age = age;
+
+            assert_relative_eq!(creature.senescence(&config), expected);
+        }
+
+        #[test]
+        fn test_immortal() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let config = Config {
+                max_lifespan: 0,
+                ..Default::default()
+            };
+            let mut creature = Creature::random(&mut rng, &config);
+            creature.age = 1_000_000;
+
+            assert_relative_eq!(creature.senescence(&config), 0.0);
+        }
+
+        #[test_case(1.0)]
+        #[test_case(2.0)]
+        fn test_no_decline(senescence_onset: f32) {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let config = Config {
+                max_lifespan: 1000,
+                senescence_onset,
+                ..Default::default()
+            };
+            let mut creature = Creature::random(&mut rng, &config);
+            creature.age = 1000;
+
+            assert_eq!(creature.senescence(&config), 0.0);
+        }
     }
 }
@@ -3,6 +3,7 @@ use crate::*;
 pub struct CreatureIndividual {
     fitness: f32,
     chromosome: ga::Chromosome,
+    behaviour: [f32; 2], // Where the Creature got to, for novelty search
 }
 
 impl ga::Individual for CreatureIndividual {
@@ -10,6 +11,7 @@ impl ga::Individual for CreatureIndividual {
         CreatureIndividual {
             fitness: 0.0,
             chromosome,
+            behaviour: [0.0; 2],
         }
     }
 
@@ -22,15 +24,27 @@ impl ga::Individual for CreatureIndividual {
     }
 }
 
+impl ga::Behaviour for CreatureIndividual {
+    fn behaviour(&self) -> &[f32] {
+        &self.behaviour
+    }
+}
+
 impl CreatureIndividual {
-    pub fn from_creature(creature: &Creature) -> CreatureIndividual {
+    pub fn from_creature(creature: &Creature, config: &Config) -> CreatureIndividual {
         CreatureIndividual {
             fitness: creature.fitness(),
-            chromosome: creature.as_chromosome(),
+            chromosome: creature.as_chromosome(config),
+            behaviour: [creature.position.x, creature.position.y],
         }
     }
 
-    pub fn into_creature(self, rng: &mut dyn RngCore, config: &Config) -> Creature {
-        Creature::from_chromosome(self.chromosome, rng, &config)
+    pub fn into_creature(
+        self,
+        brain: Inheritance,
+        rng: &mut dyn RngCore,
+        config: &Config,
+    ) -> Creature {
+        Creature::from_chromosome(self.chromosome, brain, rng, config)
     }
 }
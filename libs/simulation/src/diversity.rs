@@ -0,0 +1,118 @@
+use ga::DistanceMethod;
+
+use crate::*;
+
+/// How varied the population is, as the mean difference between pairs of
+/// sampled creatures. Both measures fall towards zero as the population
+/// converges.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Diversity {
+    pub(crate) genetic: f32, // Mean Euclidean distance between genes, over √genes
+    pub(crate) behavioural: f32, // Same between brain outputs on the probes, over √outputs
+}
+
+impl Diversity {
+    /// Compares creatures spread evenly over the population, so that
+    /// measuring doesn't draw from the simulation's rng
+    pub(crate) fn measure(
+        creatures: &[Creature],
+        probes: &[Vec<f32>],
+        config: &Config,
+    ) -> Diversity {
+        let stride = (creatures.len() / config.diversity_sample.max(1)).max(1);
+        let sample: Vec<_> = creatures
+            .iter()
+            .step_by(stride)
+            .take(config.diversity_sample)
+            .collect();
+
+        let chromosomes: Vec<_> = sample
+            .iter()
+            .map(|creature| creature.as_chromosome(config))
+            .collect();
+        let behaviours: Vec<_> = sample
+            .iter()
+            .map(|creature| creature.brain.behaviour(probes))
+            .collect();
+
+        Diversity {
+            genetic: scaled_diversity(chromosomes.iter().map(ga::Chromosome::as_slice)),
+            behavioural: scaled_diversity(behaviours.iter().map(Vec::as_slice)),
+        }
+    }
+
+    pub fn genetic(&self) -> f32 {
+        self.genetic
+    }
+
+    pub fn behavioural(&self) -> f32 {
+        self.behavioural
+    }
+}
+
+/// Mean Euclidean distance between pairs, divided by the square root of the
+/// number of values describing each member so that it doesn't grow with it
+fn scaled_diversity<'a>(members: impl Iterator<Item = &'a [f32]>) -> f32 {
+    let members: Vec<_> = members.collect();
+    let len = members.first().map_or(0, |member| member.len());
+
+    if len == 0 {
+        return 0.0;
+    }
+
+    ga::EuclideanDistance::new().diversity(&members) / (len as f32).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn probes(config: &Config) -> Vec<Vec<f32>> {
+        let inputs = Brain::inputs(&Eye::from_config(config), config);
+
+        (0..4)
+            .map(|n| (0..inputs).map(|i| ((n + i) % 3) as f32 / 3.0).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_clones() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let config = Config::default();
+        let creature = Creature::random(&mut rng, &config);
+        let creatures = vec![creature.clone(), creature];
+
+        let diversity = Diversity::measure(&creatures, &probes(&config), &config);
+        assert_eq!(diversity, Diversity::default());
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let config = Config::default();
+        let creatures: Vec<_> = (0..10)
+            .map(|_| Creature::random(&mut rng, &config))
+            .collect();
+
+        let diversity = Diversity::measure(&creatures, &probes(&config), &config);
+        assert!(diversity.genetic() > 0.1);
+        assert!(diversity.behavioural() > 0.01);
+    }
+
+    #[test]
+    fn test_disabled() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let config = Config {
+            diversity_sample: 0,
+            ..Default::default()
+        };
+        let creatures: Vec<_> = (0..10)
+            .map(|_| Creature::random(&mut rng, &config))
+            .collect();
+
+        let diversity = Diversity::measure(&creatures, &probes(&config), &config);
+        assert_eq!(diversity, Diversity::default());
+    }
+}
@@ -25,10 +25,37 @@ impl Eye {
         }
     }
 
+    /// Eye of the initial population, with the FOV from the config
+    pub(crate) fn from_config(config: &Config) -> Eye {
+        Eye::new(config.fov_range, config.fov_angle, config.eye_cells)
+    }
+
+    /// Heritable traits, in the order of the genome's "eye" segment
+    pub(crate) fn traits(&self) -> Vec<f32> {
+        vec![self.fov_range, self.fov_angle]
+    }
+
+    pub(crate) fn from_traits(traits: &[f32], config: &Config) -> Eye {
+        Eye::new(traits[0], traits[1], config.eye_cells)
+    }
+
+    pub fn fov_range(&self) -> f32 {
+        self.fov_range
+    }
+
+    pub fn fov_angle(&self) -> f32 {
+        self.fov_angle
+    }
+
     pub fn cells(&self) -> usize {
         self.cells
     }
 
+    /// Area of the field of view, relative to an Eye built from the default config
+    pub fn capability(&self, config: &Config) -> f32 {
+        (self.fov_range / config.fov_range).powi(2) * (self.fov_angle / config.fov_angle)
+    }
+
     pub fn process_vision(
         &self,
         position: na::Point2<f32>,
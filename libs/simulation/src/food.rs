@@ -12,6 +12,12 @@ impl Food {
         }
     }
 
+    pub(crate) fn on_terrain(rng: &mut dyn RngCore, terrain: &Terrain) -> Food {
+        Food {
+            position: terrain.random_position(rng),
+        }
+    }
+
     pub fn position(&self) -> na::Point2<f32> {
         self.position
     }
@@ -0,0 +1,343 @@
+use crate::*;
+
+/// Bumped whenever the meaning of existing genes changes
+pub const GENOME_VERSION: u32 = 2;
+
+const TRAIT_SCALE: f32 = 5.0; // Chromosome span of a trait's full range, relative to mutation strength
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gene {
+    /// Stored as-is, e.g. network weights
+    Raw,
+    /// Bounded trait, stored normalized to [0, TRAIT_SCALE] and clamped on decode
+    Trait { min: f32, max: f32 },
+}
+
+impl Gene {
+    fn encode(&self, value: f32) -> f32 {
+        match *self {
+            Gene::Raw => value,
+            // A trait pinned to a single value carries no information
+            Gene::Trait { min, max } if max <= min => 0.0,
+            Gene::Trait { min, max } => (value - min) / (max - min) * TRAIT_SCALE,
+        }
+    }
+
+    fn decode(&self, gene: f32) -> f32 {
+        match *self {
+            Gene::Raw => gene,
+            Gene::Trait { min, max } => min + (gene / TRAIT_SCALE).clamp(0.0, 1.0) * (max - min),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GeneSegment {
+    name: &'static str,
+    genes: Vec<Gene>,
+}
+
+impl GeneSegment {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn genes(&self) -> &[Gene] {
+        &self.genes
+    }
+
+    pub fn len(&self) -> usize {
+        self.genes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.genes.is_empty()
+    }
+}
+
+/// Describes how a Creature is laid out in its chromosome, so that
+/// chromosome lengths and slicing follow automatically from the config
+#[derive(Debug, Clone)]
+pub struct GenomeLayout {
+    version: u32,
+    segments: Vec<GeneSegment>,
+}
+
+impl GenomeLayout {
+    pub fn new(config: &Config) -> GenomeLayout {
+        let brain = GeneSegment {
+            name: "brain",
+            genes: vec![Gene::Raw; Brain::chromosome_length(config)],
+        };
+        let body = GeneSegment {
+            name: "body",
+            genes: vec![
+                // Size
+                Gene::Trait {
+                    min: config.creature_size / 5.0,
+                    max: config.creature_size * 5.0,
+                },
+                // Color
+                Gene::Trait { min: 0.0, max: 1.0 },
+            ],
+        };
+        let eye = GeneSegment {
+            name: "eye",
+            genes: vec![
+                // FOV range
+                Gene::Trait {
+                    min: config.fov_range_min,
+                    max: config.fov_range_max,
+                },
+                // FOV angle
+                Gene::Trait {
+                    min: config.fov_angle_min,
+                    max: config.fov_angle_max,
+                },
+            ],
+        };
+
+        GenomeLayout {
+            version: GENOME_VERSION,
+            segments: vec![brain, body, eye],
+        }
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn segments(&self) -> &[GeneSegment] {
+        &self.segments
+    }
+
+    pub fn segment(&self, name: &str) -> Option<&GeneSegment> {
+        self.segments.iter().find(|segment| segment.name == name)
+    }
+
+    /// Total number of genes in a chromosome following this layout
+    pub fn len(&self) -> usize {
+        self.segments.iter().map(GeneSegment::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Builds a chromosome from the decoded values of every segment
+    pub(crate) fn encode(&self, values: &[(&str, Vec<f32>)]) -> ga::Chromosome {
+        self.segments
+            .iter()
+            .flat_map(|segment| {
+                let (_, values) = values
+                    .iter()
+                    .find(|(name, _)| *name == segment.name)
+                    .unwrap_or_else(|| panic!("Missing values for segment '{}'", segment.name));
+
+                assert_eq!(
+                    values.len(),
+                    segment.len(),
+                    "Wrong number of values for segment '{}'",
+                    segment.name
+                );
+
+                segment
+                    .genes
+                    .iter()
+                    .zip(values)
+                    .map(|(gene, value)| gene.encode(*value))
+            })
+            .collect()
+    }
+
+    /// Splits a chromosome into segments, decoding and clamping every gene
+    pub(crate) fn decode(&self, chromosome: &ga::Chromosome) -> Genome {
+        assert_eq!(
+            chromosome.len(),
+            self.len(),
+            "Chromosome does not match genome layout v{}",
+            self.version
+        );
+
+        let mut genes = chromosome.iter();
+        let segments = self
+            .segments
+            .iter()
+            .map(|segment| {
+                let values = segment
+                    .genes
+                    .iter()
+                    .zip(&mut genes)
+                    .map(|(gene, value)| gene.decode(*value))
+                    .collect();
+
+                (segment.name, values)
+            })
+            .collect();
+
+        Genome { segments }
+    }
+}
+
+/// Decoded values of every segment of a chromosome
+#[derive(Debug, Clone)]
+pub(crate) struct Genome {
+    segments: Vec<(&'static str, Vec<f32>)>,
+}
+
+impl Genome {
+    pub(crate) fn segment(&self, name: &str) -> &[f32] {
+        self.segments
+            .iter()
+            .find(|(segment, _)| *segment == name)
+            .map(|(_, values)| values.as_slice())
+            .unwrap_or_else(|| panic!("Unknown genome segment '{}'", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    fn config() -> Config {
+        Config {
+            creature_size: 0.01,
+            fov_range_min: 0.1,
+            fov_range_max: 0.5,
+            fov_angle_min: FRAC_PI_2,
+            fov_angle_max: PI,
+            ..Default::default()
+        }
+    }
+
+    fn values(brain: usize) -> Vec<(&'static str, Vec<f32>)> {
+        vec![
+            ("eye", vec![0.2, 0.75 * PI]),
+            ("body", vec![0.02, 0.25]),
+            ("brain", (0..brain).map(|n| n as f32 - 10.0).collect()),
+        ]
+    }
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let config = config();
+            let layout = GenomeLayout::new(&config);
+            let brain = Brain::chromosome_length(&config);
+
+            assert_eq!(layout.version(), GENOME_VERSION);
+            assert_eq!(layout.segment("brain").unwrap().len(), brain);
+            assert_eq!(layout.segment("body").unwrap().len(), 2);
+            assert_eq!(layout.segment("eye").unwrap().len(), 2);
+            assert!(layout.segment("tail").is_none());
+            assert_eq!(layout.len(), brain + 4);
+        }
+
+        #[test]
+        fn test_follows_brain_topology() {
+            let config = config();
+            let wider = Config {
+                pheromone_channels: 2,
+                ..super::config()
+            };
+
+            let layout = GenomeLayout::new(&config);
+            let wider_layout = GenomeLayout::new(&wider);
+
+            assert!(wider_layout.len() > layout.len());
+            assert_eq!(
+                wider_layout.segment("brain").unwrap().len(),
+                Brain::chromosome_length(&wider)
+            );
+        }
+    }
+
+    mod encode {
+        use super::*;
+
+        #[test]
+        fn test_round_trip() {
+            let config = config();
+            let layout = GenomeLayout::new(&config);
+            let brain = Brain::chromosome_length(&config);
+
+            let chromosome = layout.encode(&values(brain));
+            assert_eq!(chromosome.len(), layout.len());
+
+            let genome = layout.decode(&chromosome);
+            let values = values(brain);
+            assert_relative_eq!(genome.segment("brain"), values[2].1.as_slice());
+            assert_relative_eq!(
+                genome.segment("body"),
+                [0.02, 0.25].as_ref(),
+                epsilon = 1e-6
+            );
+            assert_relative_eq!(
+                genome.segment("eye"),
+                [0.2, 0.75 * PI].as_ref(),
+                epsilon = 1e-6
+            );
+        }
+
+        #[test]
+        fn test_pinned_trait() {
+            let config = Config {
+                fov_range_min: 0.3,
+                fov_range_max: 0.3,
+                ..config()
+            };
+            let layout = GenomeLayout::new(&config);
+            let brain = Brain::chromosome_length(&config);
+
+            let chromosome = layout.encode(&values(brain));
+            assert!(chromosome.iter().all(|gene| gene.is_finite()));
+
+            let genome = layout.decode(&chromosome);
+            assert_relative_eq!(genome.segment("eye")[0], 0.3);
+        }
+
+        #[test]
+        #[should_panic(expected = "Wrong number of values for segment 'body'")]
+        fn test_wrong_segment_length() {
+            let config = config();
+            let layout = GenomeLayout::new(&config);
+            let mut values = values(Brain::chromosome_length(&config));
+            values[1].1.push(1.0);
+
+            layout.encode(&values);
+        }
+    }
+
+    mod decode {
+        use super::*;
+
+        #[test]
+        fn test_clamps_traits() {
+            let config = config();
+            let layout = GenomeLayout::new(&config);
+            let brain = Brain::chromosome_length(&config);
+
+            let chromosome = (0..brain)
+                .map(|_| 100.0)
+                .chain(vec![-3.0, 100.0, 100.0, -3.0])
+                .collect();
+            let genome = layout.decode(&chromosome);
+
+            assert!(genome.segment("brain").iter().all(|gene| *gene == 100.0));
+            assert_relative_eq!(genome.segment("body"), [0.002, 1.0].as_ref());
+            assert_relative_eq!(genome.segment("eye"), [0.5, FRAC_PI_2].as_ref());
+        }
+
+        #[test]
+        #[should_panic(expected = "Chromosome does not match genome layout")]
+        fn test_wrong_length() {
+            let layout = GenomeLayout::new(&config());
+
+            layout.decode(&vec![1.0, 2.0].into_iter().collect());
+        }
+    }
+}
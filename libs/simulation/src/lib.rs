@@ -1,21 +1,39 @@
 use std::cmp::Ordering;
 
 use nalgebra as na;
-use rand::{Rng, RngCore};
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
 
 use lib_genetic_algorithm as ga;
+use lib_neat as neat;
 use lib_neural_network as nn;
 
-use self::creature_individual::*;
-pub use self::{body::*, brain::*, config::*, creature::*, eye::*, food::*, world::*};
+pub use lib_neural_network::{
+    Activation, Initializer, LayerKind, LayerTopology, Loss, Optimizer, Sample, Trainer,
+};
+
+pub use self::{
+    body::*, brain::*, config::*, creature::*, diversity::*, eye::*, food::*, genome::*,
+    metabolism::*, pheromone::*, terrain::*, world::*,
+};
+use self::{collision::*, creature_individual::*, senses::*, signal::*, spatial::*};
 
 mod body;
 mod brain;
+mod collision;
 mod config;
 mod creature;
 mod creature_individual;
+mod diversity;
 mod eye;
 mod food;
+mod genome;
+mod metabolism;
+mod pheromone;
+mod senses;
+mod signal;
+mod spatial;
+mod terrain;
 mod world;
 
 const UPPER_BOUND_X: f32 = 1.0; // Upper bound for Creature position
@@ -27,12 +45,25 @@ pub struct Simulation {
     world: World,
     ga: ga::GeneticAlgorithm<ga::RouletteWheelSelection>,
     age: usize,
+    innovations: neat::InnovationHistory,
     config: Config,
+    // Buffers reused by every tick's brain propagation
+    brain_inputs: Vec<f32>,
+    brain_outputs: Vec<f32>,
+    scratch: nn::Scratch,
+    probes: Vec<Vec<f32>>, // Brain inputs on which behaviours are compared
+    diversity: Diversity,
+    novelty: Option<ga::NoveltySearch>, // Scores parents when novelty_weight > 0
 }
 
 impl Simulation {
+    /// Panics if the config doesn't pass `Config::validate`
     pub fn random(rng: &mut dyn RngCore, config: Option<Config>) -> Simulation {
         let config = config.unwrap_or_default();
+        if let Err(err) = config.validate() {
+            panic!("Invalid config: {}", err);
+        }
+
         let world = World::random(rng, &config);
         let ga = ga::GeneticAlgorithm::new(
             ga::RouletteWheelSelection::new(),
@@ -40,11 +71,40 @@ impl Simulation {
             ga::GaussianMutation::new(config.mutation_rate, config.mutation_strength),
         );
 
+        let inputs = Brain::inputs(&Eye::from_config(&config), &config);
+        // Drawn from a fixed seed, so that every run is measured the same way
+        // and the simulation's rng is left alone
+        let mut probe_rng = StdRng::seed_from_u64(0);
+        let probes = (0..config.diversity_probes)
+            .map(|_| {
+                (0..inputs)
+                    .map(|_| probe_rng.gen_range(0.0..=1.0))
+                    .collect()
+            })
+            .collect();
+
+        let novelty = (config.novelty_weight > 0.0).then(|| {
+            ga::NoveltySearch::new(
+                config.novelty_neighbours.max(1),
+                config.novelty_threshold,
+                config.novelty_archive,
+                config.novelty_weight.min(1.0),
+                ga::EuclideanDistance::new(),
+            )
+        });
+
         Simulation {
             world,
             ga,
             age: 0,
+            innovations: Brain::innovations(&config),
             config,
+            brain_inputs: Vec::new(),
+            brain_outputs: Vec::new(),
+            scratch: nn::Scratch::default(),
+            probes,
+            diversity: Diversity::default(),
+            novelty,
         }
     }
 
@@ -56,45 +116,203 @@ impl Simulation {
         &self.age
     }
 
+    /// Diversity of the population as of the latest measurement, taken
+    /// every `diversity_interval` steps
+    pub fn diversity(&self) -> &Diversity {
+        &self.diversity
+    }
+
+    /// Pre-trains every brain by imitation before evolution takes over,
+    /// returning the mean loss of the last epoch across creatures. Only
+    /// layered brains made of dense layers, without plasticity, can be
+    /// pre-trained; other brains are left as they are, and None is returned
+    /// when no brain was trained. Nothing is trained either when there are no
+    /// samples or when they don't match the brains' inputs and outputs.
+    pub fn pretrain(
+        &mut self,
+        rng: &mut dyn RngCore,
+        samples: &[nn::Sample],
+        trainer: &nn::Trainer,
+    ) -> Option<f32> {
+        let inputs = Brain::inputs(&self.world.creatures.first()?.eye, &self.config);
+        let outputs = Brain::outputs(&self.config);
+
+        if samples.is_empty()
+            || samples
+                .iter()
+                .any(|sample| sample.inputs.len() != inputs || sample.targets.len() != outputs)
+        {
+            return None;
+        }
+
+        let losses: Vec<_> = self
+            .world
+            .creatures
+            .iter_mut()
+            .filter_map(|creature| {
+                creature
+                    .brain
+                    .imitate(rng, samples, trainer, &creature.eye, &self.config)?
+                    .last()
+                    .copied()
+            })
+            .collect();
+
+        if losses.is_empty() {
+            return None;
+        }
+
+        Some(losses.iter().sum::<f32>() / losses.len() as f32)
+    }
+
+    /// What the brain of the creature at `idx` currently senses, followed by
+    /// the activations of each of its hidden layers and finally its outputs.
+    /// These are the activations the next tick will start from, given the
+    /// brain's current memory; the brain itself is left untouched.
+    pub fn brain_trace(&self, idx: usize) -> Option<Vec<Vec<f32>>> {
+        let creature = self.world.creatures.get(idx)?;
+        let signal = receive_signal(&self.world.creatures, idx, &self.config);
+        let inputs = brain_inputs(
+            creature,
+            signal,
+            &self.world.foods,
+            &self.world.pheromones,
+            &self.config,
+        )
+        .collect();
+
+        Some(creature.brain.trace(inputs))
+    }
+
     pub fn step(&mut self, rng: &mut dyn RngCore) {
         self.process_brains();
+        self.process_pheromones();
         self.process_movement();
+        self.process_crowding();
         self.process_collisions(rng);
+        self.process_aging();
         self.process_death();
         self.process_evolution(rng);
         self.process_death();
+        self.process_diversity();
 
         self.age += 1;
     }
 
     fn process_brains(&mut self) {
-        for creature in &mut self.world.creatures {
-            let vision = creature.eye.process_vision(
-                creature.position,
-                creature.body.rotation,
+        let signals = receive_signals(&self.world.creatures, &self.config);
+
+        self.brain_inputs.clear();
+
+        for (creature, signal) in self.world.creatures.iter_mut().zip(signals) {
+            self.brain_inputs.extend(brain_inputs(
+                creature,
+                signal,
                 &self.world.foods,
+                &self.world.pheromones,
+                &self.config,
+            ));
+
+            // Whatever led to the latest meals is reinforced before acting again
+            creature.brain.learn(
+                self.config.plasticity_baseline
+                    + self.config.plasticity_reward * creature.meals as f32,
+            );
+            creature.meals = 0;
+        }
+
+        let Some(first) = self.world.creatures.first() else {
+            return;
+        };
+        let inputs_per_brain = Brain::inputs(&first.eye, &self.config);
+        let outputs_per_brain = Brain::outputs(&self.config);
+
+        Brain::propagate_batch(
+            self.world
+                .creatures
+                .iter_mut()
+                .map(|creature| &mut creature.brain),
+            &self.brain_inputs,
+            inputs_per_brain,
+            &mut self.brain_outputs,
+            &mut self.scratch,
+        );
+
+        for (creature, update) in self
+            .world
+            .creatures
+            .iter_mut()
+            .zip(self.brain_outputs.chunks(outputs_per_brain))
+        {
+            creature.body.thrust = update[0].clamp(-1.0, 1.0);
+            creature.body.torque = update[1].clamp(-1.0, 1.0);
+
+            let terrain = self.world.terrain.sample(creature.position);
+            let senescence = creature.senescence(&self.config);
+            creature.body.process_energy(
+                &creature.brain,
+                &creature.eye,
+                &terrain,
+                senescence,
+                &self.config,
             );
 
-            let update = creature.brain.nn.propagate(vision);
-            let speed = update[0].clamp(-self.config.speed_accel, self.config.speed_accel);
-            let rotation = update[1].clamp(-self.config.rotation_accel, self.config.rotation_accel);
+            let (deposits, signal) = update[2..].split_at(self.config.pheromone_channels);
 
-            creature.body.speed =
-                (creature.body.speed + speed).clamp(self.config.speed_min, self.config.speed_max);
-            creature.body.rotation = na::Rotation2::new(creature.body.rotation.angle() + rotation);
+            for (channel, amount) in deposits.iter().enumerate() {
+                if *amount > 0.0 {
+                    self.world.pheromones.deposit(
+                        creature.position,
+                        channel,
+                        amount * self.config.pheromone_deposit,
+                    );
+                }
+            }
 
-            creature.body.process_energy(rotation, &self.config);
+            creature.signal = signal.to_vec();
+            if self.config.signal_color {
+                creature.tint = signal.first().map(|value| (value + 1.0) / 2.0);
+            }
         }
     }
 
+    fn process_pheromones(&mut self) {
+        self.world.pheromones.step(
+            self.config.pheromone_diffusion,
+            self.config.pheromone_evaporation,
+        );
+    }
+
     fn process_movement(&mut self) {
         for creature in &mut self.world.creatures {
-            creature.position += creature.body.rotation
-                * na::Vector2::new(
-                    creature.body.speed * (creature.body.size / self.config.creature_size).tanh(),
-                    0.0,
-                );
+            let terrain = self.world.terrain.sample(creature.position);
+            let traction = (1.0 - terrain.friction)
+                * (1.0 - self.config.senescence_speed_factor * creature.senescence(&self.config));
+
+            let position =
+                creature.position + creature.body.process_physics(traction, &self.config);
+
+            creature.position.x = position.x.clamp(LOWER_BOUND_X, UPPER_BOUND_X);
+            creature.position.y = position.y.clamp(LOWER_BOUND_Y, UPPER_BOUND_Y);
+
+            // Walls absorb any momentum carrying a Creature out of bounds
+            if creature.position.x != position.x {
+                creature.body.velocity.x = 0.0;
+            }
+            if creature.position.y != position.y {
+                creature.body.velocity.y = 0.0;
+            }
+        }
+    }
 
+    fn process_crowding(&mut self) {
+        if !self.config.creature_collisions {
+            return;
+        }
+
+        resolve_collisions(&mut self.world.creatures, &self.config);
+
+        for creature in &mut self.world.creatures {
             creature.position.x = creature.position.x.clamp(LOWER_BOUND_X, UPPER_BOUND_X);
             creature.position.y = creature.position.y.clamp(LOWER_BOUND_Y, UPPER_BOUND_Y);
         }
@@ -106,9 +324,10 @@ impl Simulation {
                 let distance = na::distance(&creature.position, &food.position);
 
                 if distance <= (creature.body.size + self.config.food_size) / 2.0 {
-                    creature.body.energy += self.config.food_energy;
+                    creature.body.eat(self.config.food_energy, &self.config);
                     creature.satiation += 1;
-                    food.position = rng.gen();
+                    creature.meals += 1;
+                    food.position = self.world.terrain.random_position(rng);
                 }
             }
         }
@@ -121,16 +340,22 @@ impl Simulation {
             creatures.into_iter().enumerate().collect();
         creatures_with_idx
             .retain(|(_, creature)| creature.body.energy >= self.config.reproduction_threshold);
+        let scores = self.parent_scores(&creatures_with_idx);
 
         let mut reproduced_indices = Vec::new();
         let mut new_creatures = Vec::new();
         for (idx, creature) in self.world.creatures.iter().enumerate() {
-            if creature.body.energy >= self.config.reproduction_threshold {
+            if let Some(score) = scores[idx] {
                 // Prevent duplicated reproduction
                 if reproduced_indices.contains(&idx) {
                     continue;
                 }
 
+                // With novelty search, only seek a partner with a chance equal to the score
+                if self.novelty.is_some() && !rng.gen_bool(score as f64) {
+                    continue;
+                }
+
                 // Find nearest creature with enough energy
                 let (nearest_creature_idx, nearest_creature) = creatures_with_idx
                     .iter()
@@ -154,15 +379,26 @@ impl Simulation {
                     continue;
                 }
 
+                let brain = Brain::crossover(
+                    rng,
+                    &creature.brain,
+                    score,
+                    &nearest_creature.brain,
+                    scores[*nearest_creature_idx].unwrap_or_default(),
+                    &mut self.innovations,
+                    &self.config,
+                );
                 let mut new_creature = self
                     .ga
                     .breed(
                         rng,
-                        CreatureIndividual::from_creature(creature),
-                        CreatureIndividual::from_creature(nearest_creature),
+                        CreatureIndividual::from_creature(creature, &self.config),
+                        CreatureIndividual::from_creature(nearest_creature, &self.config),
                     )
-                    .into_creature(rng, &self.config);
-                new_creature.body.energy = self.config.reproduction_cost * 2.0; // Energy from parents
+                    .into_creature(brain, rng, &self.config);
+                new_creature.body.energy =
+                    (self.config.reproduction_cost * 2.0) // Energy from parents
+                        .min(new_creature.body.energy_capacity(&self.config));
                 new_creature.position = na::center(&creature.position, &nearest_creature.position);
                 new_creature.generation = creature.generation.max(nearest_creature.generation) + 1;
                 new_creatures.push(new_creature);
@@ -179,10 +415,52 @@ impl Simulation {
         self.world.creatures.extend(new_creatures);
     }
 
+    /// Scores the creatures ready to reproduce, by index, with their fitness
+    /// or, with novelty search, with a blend of how far from the others and
+    /// the archive each ended up and its fitness, scaled to 0..=1
+    fn parent_scores(&mut self, candidates: &[(usize, Creature)]) -> Vec<Option<f32>> {
+        let mut scores = vec![None; self.world.creatures.len()];
+
+        let Some(novelty) = &mut self.novelty else {
+            for (idx, creature) in candidates.iter() {
+                scores[*idx] = Some(creature.fitness());
+            }
+            return scores;
+        };
+
+        let individuals = candidates
+            .iter()
+            .map(|(_, creature)| CreatureIndividual::from_creature(creature, &self.config))
+            .collect();
+
+        for ((idx, _), novel) in candidates.iter().zip(novelty.evaluate(individuals)) {
+            scores[*idx] = Some(ga::Individual::fitness(&novel).clamp(0.0, 1.0));
+        }
+
+        scores
+    }
+
+    fn process_diversity(&mut self) {
+        // An interval of 0 has no remainder, which disables measuring
+        if self.age.checked_rem(self.config.diversity_interval) != Some(0) {
+            return;
+        }
+
+        self.diversity = Diversity::measure(&self.world.creatures, &self.probes, &self.config);
+    }
+
+    fn process_aging(&mut self) {
+        for creature in &mut self.world.creatures {
+            creature.age += 1;
+        }
+    }
+
     fn process_death(&mut self) {
-        self.world
-            .creatures
-            .retain(|creature| creature.body.energy > 0.0);
+        let max_lifespan = self.config.max_lifespan;
+
+        self.world.creatures.retain(|creature| {
+            creature.body.energy > 0.0 && (max_lifespan == 0 || creature.age < max_lifespan)
+        });
     }
 
     /// Step until end of current generation
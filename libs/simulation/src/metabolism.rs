@@ -0,0 +1,149 @@
+use crate::*;
+
+/// Energy spent by a Creature during a single tick, broken down by cause
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EnergyReport {
+    pub(crate) basal: f32,    // Cost of staying alive, scaling with size
+    pub(crate) movement: f32, // Cost of moving, scaling with speed² and mass
+    pub(crate) rotation: f32, // Cost of turning, scaling with torque²
+    pub(crate) brain: f32,    // Cost of running the brain, scaling with its parameter count
+    pub(crate) vision: f32,   // Cost of seeing, scaling with the field of view area
+}
+
+impl EnergyReport {
+    /// Computes each metabolic term, scaled by `factor` to account for
+    /// environmental and age-related effects
+    pub(crate) fn new(
+        body: &Body,
+        brain: &Brain,
+        eye: &Eye,
+        factor: f32,
+        config: &Config,
+    ) -> EnergyReport {
+        let mass = body.mass(config);
+        let speed = body.speed() / config.speed_max;
+
+        EnergyReport {
+            basal: factor * config.basal_cost * mass.powf(0.75),
+            movement: factor * config.movement_cost * mass * speed.powi(2),
+            rotation: factor * config.rotation_cost * body.torque.powi(2),
+            brain: factor * config.brain_cost * brain.parameters() as f32,
+            vision: factor * config.vision_cost * eye.capability(config),
+        }
+    }
+
+    pub fn basal(&self) -> f32 {
+        self.basal
+    }
+
+    pub fn movement(&self) -> f32 {
+        self.movement
+    }
+
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    pub fn brain(&self) -> f32 {
+        self.brain
+    }
+
+    pub fn vision(&self) -> f32 {
+        self.vision
+    }
+
+    pub fn total(&self) -> f32 {
+        self.basal + self.movement + self.rotation + self.brain + self.vision
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn config() -> Config {
+        Config {
+            creature_size: 0.01,
+            speed_max: 0.002,
+            fov_range: 0.2,
+            fov_angle: 2.0,
+            basal_cost: 0.1,
+            movement_cost: 0.2,
+            rotation_cost: 0.3,
+            brain_cost: 0.01,
+            vision_cost: 0.4,
+            ..Default::default()
+        }
+    }
+
+    fn creature(size: f32, speed: f32, torque: f32, config: &Config) -> Creature {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut creature = Creature::random(&mut rng, config);
+        creature.body.size = size;
+        creature.body.velocity = na::Vector2::new(0.0, speed);
+        creature.body.torque = torque;
+        creature
+    }
+
+    fn report(creature: &Creature, factor: f32, config: &Config) -> EnergyReport {
+        EnergyReport::new(
+            &creature.body,
+            &creature.brain,
+            &creature.eye,
+            factor,
+            config,
+        )
+    }
+
+    #[test]
+    fn test() {
+        let config = config();
+        let mut creature = creature(0.02, 0.001, -0.5, &config);
+        creature.eye = Eye::new(0.1, 3.0, config.eye_cells);
+        let report = report(&creature, 1.0, &config);
+
+        assert_relative_eq!(report.basal(), 0.1 * 4.0f32.powf(0.75));
+        assert_relative_eq!(report.movement(), 0.2 * 4.0 * 0.25);
+        assert_relative_eq!(report.rotation(), 0.3 * 0.25);
+        assert_relative_eq!(report.brain(), 0.01 * creature.brain.parameters() as f32);
+        assert_relative_eq!(report.vision(), 0.4 * 0.25 * 1.5);
+        assert_relative_eq!(
+            report.total(),
+            report.basal()
+                + report.movement()
+                + report.rotation()
+                + report.brain()
+                + report.vision()
+        );
+    }
+
+    #[test]
+    fn test_factor_scales_every_term() {
+        let config = config();
+        let creature = creature(0.01, 0.002, 0.5, &config);
+        let base = report(&creature, 1.0, &config);
+        let scaled = report(&creature, 2.5, &config);
+
+        assert_relative_eq!(scaled.basal(), 2.5 * base.basal());
+        assert_relative_eq!(scaled.movement(), 2.5 * base.movement());
+        assert_relative_eq!(scaled.rotation(), 2.5 * base.rotation());
+        assert_relative_eq!(scaled.brain(), 2.5 * base.brain());
+        assert_relative_eq!(scaled.vision(), 2.5 * base.vision());
+    }
+
+    #[test]
+    fn test_larger_bodies_cost_more_to_move() {
+        let config = config();
+        let small = report(&creature(0.005, 0.001, 0.0, &config), 1.0, &config);
+        let large = report(&creature(0.015, 0.001, 0.0, &config), 1.0, &config);
+
+        assert!(large.basal() > small.basal());
+        assert!(large.movement() > small.movement());
+        assert_relative_eq!(large.rotation(), 0.0);
+        assert_relative_eq!(large.brain(), small.brain());
+        assert_relative_eq!(large.vision(), small.vision());
+    }
+}
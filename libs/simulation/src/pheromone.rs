@@ -0,0 +1,206 @@
+use crate::*;
+use std::f32::consts::FRAC_PI_4;
+
+#[derive(Debug, Clone)]
+pub struct PheromoneField {
+    resolution: usize,
+    channels: usize,
+    values: Vec<f32>, // Channel-major grid of concentrations
+}
+
+impl PheromoneField {
+    pub fn new(resolution: usize, channels: usize) -> PheromoneField {
+        assert!(resolution > 0);
+
+        PheromoneField {
+            resolution,
+            channels,
+            values: vec![0.0; channels * resolution * resolution],
+        }
+    }
+
+    pub fn resolution(&self) -> usize {
+        self.resolution
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Returns the concentration grid of a single channel
+    pub fn values(&self, channel: usize) -> &[f32] {
+        let len = self.resolution * self.resolution;
+        &self.values[channel * len..(channel + 1) * len]
+    }
+
+    /// Returns the concentration of every channel at the given world position
+    pub fn sample(&self, position: na::Point2<f32>) -> Vec<f32> {
+        let idx = grid_index(position, self.resolution);
+
+        (0..self.channels)
+            .map(|channel| self.values(channel)[idx])
+            .collect()
+    }
+
+    /// Samples every channel at a left and a right antenna ahead of the given position,
+    /// returning all left concentrations followed by all right concentrations
+    pub(crate) fn smell(
+        &self,
+        position: na::Point2<f32>,
+        rotation: na::Rotation2<f32>,
+        antenna: f32,
+    ) -> Vec<f32> {
+        [FRAC_PI_4, -FRAC_PI_4]
+            .iter()
+            .flat_map(|angle| {
+                let offset =
+                    na::Rotation2::new(rotation.angle() + angle) * na::Vector2::new(antenna, 0.0);
+                self.sample(position + offset)
+            })
+            .collect()
+    }
+
+    pub(crate) fn deposit(&mut self, position: na::Point2<f32>, channel: usize, amount: f32) {
+        assert!(channel < self.channels);
+
+        let idx =
+            channel * self.resolution * self.resolution + grid_index(position, self.resolution);
+        self.values[idx] += amount;
+    }
+
+    /// Spreads each cell towards its neighbours, then evaporates a fraction of every cell.
+    /// Edges reflect, so diffusion alone conserves the total amount in each channel.
+    pub fn step(&mut self, diffusion: f32, evaporation: f32) {
+        assert!((0.0..=1.0).contains(&diffusion));
+        assert!((0.0..=1.0).contains(&evaporation));
+
+        let r = self.resolution;
+        let len = r * r;
+        let mut next = vec![0.0; self.values.len()];
+
+        for channel in 0..self.channels {
+            let offset = channel * len;
+            let values = &self.values[offset..offset + len];
+
+            for y in 0..r {
+                for x in 0..r {
+                    let value = values[y * r + x];
+                    let neighbour = |nx: usize, ny: usize| values[ny * r + nx];
+
+                    let neighbours = neighbour(x.saturating_sub(1), y)
+                        + neighbour((x + 1).min(r - 1), y)
+                        + neighbour(x, y.saturating_sub(1))
+                        + neighbour(x, (y + 1).min(r - 1));
+
+                    let diffused = value + diffusion * (neighbours / 4.0 - value);
+                    next[offset + y * r + x] = diffused * (1.0 - evaporation);
+                }
+            }
+        }
+
+        self.values = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn field() -> PheromoneField {
+        let mut field = PheromoneField::new(5, 2);
+        field.deposit(na::Point2::new(0.5, 0.5), 0, 1.0);
+        field.deposit(na::Point2::new(0.0, 0.0), 1, 2.0);
+        field
+    }
+
+    mod deposit {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let field = field();
+
+            assert_eq!(field.sample(na::Point2::new(0.5, 0.5)), vec![1.0, 0.0]);
+            assert_eq!(field.sample(na::Point2::new(0.1, 0.1)), vec![0.0, 2.0]);
+            assert_eq!(field.sample(na::Point2::new(1.0, 1.0)), vec![0.0, 0.0]);
+        }
+    }
+
+    mod smell {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut field = PheromoneField::new(10, 1);
+            field.deposit(na::Point2::new(0.55, 0.45), 0, 1.0);
+
+            let smell = field.smell(na::Point2::new(0.5, 0.5), na::Rotation2::new(0.0), 0.07);
+            assert_eq!(smell, vec![0.0, 1.0]);
+
+            let smell = field.smell(
+                na::Point2::new(0.5, 0.5),
+                na::Rotation2::new(-FRAC_PI_2),
+                0.07,
+            );
+            assert_eq!(smell, vec![1.0, 0.0]);
+        }
+    }
+
+    mod step {
+        use super::*;
+
+        #[test]
+        fn test_diffusion_conserves_mass() {
+            let mut field = field();
+
+            for _ in 0..10 {
+                field.step(0.5, 0.0);
+            }
+
+            assert_relative_eq!(field.values(0).iter().sum::<f32>(), 1.0, epsilon = 1e-5);
+            assert_relative_eq!(field.values(1).iter().sum::<f32>(), 2.0, epsilon = 1e-5);
+        }
+
+        #[test]
+        fn test_diffusion_spreads_to_neighbours() {
+            let mut field = field();
+            field.step(0.5, 0.0);
+
+            let values = field.values(0);
+            assert_relative_eq!(values[12], 0.5);
+            assert_relative_eq!(values[7], 0.125);
+            assert_relative_eq!(values[11], 0.125);
+            assert_relative_eq!(values[13], 0.125);
+            assert_relative_eq!(values[17], 0.125);
+            assert_relative_eq!(values[6], 0.0);
+        }
+
+        #[test]
+        fn test_evaporation() {
+            let mut field = field();
+
+            for _ in 0..3 {
+                field.step(0.0, 0.5);
+            }
+
+            assert_relative_eq!(field.values(0)[12], 0.125);
+            assert_relative_eq!(field.values(1)[0], 0.25);
+        }
+
+        #[test]
+        fn test_is_deterministic() {
+            let mut a = field();
+            let mut b = field();
+
+            for _ in 0..20 {
+                a.step(0.3, 0.05);
+                b.step(0.3, 0.05);
+            }
+
+            assert_eq!(a.values(0), b.values(0));
+            assert_eq!(a.values(1), b.values(1));
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use crate::*;
+use std::f32::consts::TAU;
+
+/// Everything a Creature's brain receives, given the signal reaching it:
+/// vision, smell, signals and proprioception, in the order of `Brain::inputs`
+pub(crate) fn brain_inputs(
+    creature: &Creature,
+    signal: Vec<f32>,
+    foods: &[Food],
+    pheromones: &PheromoneField,
+    config: &Config,
+) -> impl Iterator<Item = f32> {
+    let vision = creature
+        .eye
+        .process_vision(creature.position, creature.body.rotation, foods);
+    let smell = pheromones.smell(
+        creature.position,
+        creature.body.rotation,
+        config.pheromone_antenna,
+    );
+
+    vision
+        .into_iter()
+        .chain(smell)
+        .chain(signal)
+        .chain(proprioception(creature, config))
+}
+
+/// Number of internal-state inputs enabled in the config
+pub(crate) fn proprioceptive_inputs(config: &Config) -> usize {
+    config.sense_energy as usize
+        + config.sense_speed as usize
+        + config.sense_age as usize
+        + 2 * config.sense_walls as usize
+        + 2 * config.sense_clock as usize
+}
+
+/// Senses a Creature's own state, in the order:
+/// normalized energy, normalized speed, normalized age,
+/// wall proximity ahead and nearest wall proximity, bias and clock oscillator
+pub(crate) fn proprioception(creature: &Creature, config: &Config) -> Vec<f32> {
+    let mut inputs = Vec::with_capacity(proprioceptive_inputs(config));
+
+    if config.sense_energy {
+        inputs.push(creature.body.energy / creature.body.energy_capacity(config));
+    }
+
+    if config.sense_speed {
+        inputs.push(creature.body.speed() / config.speed_max);
+    }
+
+    if config.sense_age {
+        inputs.push(if config.max_lifespan == 0 {
+            0.0
+        } else {
+            creature.age as f32 / config.max_lifespan as f32
+        });
+    }
+
+    if config.sense_walls {
+        let ahead = wall_distance_ahead(creature.position, creature.body.rotation);
+        let nearest = [
+            creature.position.x - LOWER_BOUND_X,
+            UPPER_BOUND_X - creature.position.x,
+            creature.position.y - LOWER_BOUND_Y,
+            UPPER_BOUND_Y - creature.position.y,
+        ]
+        .into_iter()
+        .fold(f32::INFINITY, f32::min);
+
+        inputs.push(proximity(ahead, config.fov_range));
+        inputs.push(proximity(nearest, config.fov_range));
+    }
+
+    if config.sense_clock {
+        let phase = creature.age as f32 / config.clock_period.max(1) as f32;
+        inputs.push(1.0);
+        inputs.push((TAU * phase).sin());
+    }
+
+    inputs
+}
+
+/// Distance travelled along the heading before leaving the world
+fn wall_distance_ahead(position: na::Point2<f32>, rotation: na::Rotation2<f32>) -> f32 {
+    let direction = rotation * na::Vector2::x();
+
+    let x = if direction.x > 0.0 {
+        (UPPER_BOUND_X - position.x) / direction.x
+    } else if direction.x < 0.0 {
+        (LOWER_BOUND_X - position.x) / direction.x
+    } else {
+        f32::INFINITY
+    };
+    let y = if direction.y > 0.0 {
+        (UPPER_BOUND_Y - position.y) / direction.y
+    } else if direction.y < 0.0 {
+        (LOWER_BOUND_Y - position.y) / direction.y
+    } else {
+        f32::INFINITY
+    };
+
+    x.min(y).max(0.0)
+}
+
+/// 1 when touching, falling linearly to 0 at `range`
+fn proximity(distance: f32, range: f32) -> f32 {
+    (1.0 - distance / range).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    fn config() -> Config {
+        Config {
+            sense_energy: true,
+            sense_speed: true,
+            sense_age: true,
+            sense_walls: true,
+            sense_clock: true,
+            clock_period: 100,
+            max_lifespan: 1000,
+            speed_max: 0.01,
+            fov_range: 0.2,
+            creature_size: 0.01,
+            energy_capacity: 200.0,
+            ..Default::default()
+        }
+    }
+
+    fn creature(config: &Config) -> Creature {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut creature = Creature::random(&mut rng, config);
+        creature.position = na::Point2::new(0.95, 0.5);
+        creature.body.size = 0.01;
+        creature.body.rotation = na::Rotation2::new(0.0);
+        creature.body.velocity = na::Vector2::new(0.0, 0.005);
+        creature.body.energy = 50.0;
+        creature.age = 25;
+        creature
+    }
+
+    #[test]
+    fn test() {
+        let config = config();
+        let inputs = proprioception(&creature(&config), &config);
+
+        assert_eq!(inputs.len(), proprioceptive_inputs(&config));
+        assert_relative_eq!(
+            inputs.as_slice(),
+            [0.25, 0.5, 0.025, 0.75, 0.75, 1.0, 1.0].as_ref(),
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn test_disabled() {
+        let config = Config {
+            sense_energy: false,
+            sense_speed: false,
+            sense_age: false,
+            sense_walls: false,
+            sense_clock: false,
+            ..Default::default()
+        };
+
+        assert_eq!(proprioceptive_inputs(&config), 0);
+        assert!(proprioception(&creature(&config), &config).is_empty());
+    }
+
+    mod wall_distance_ahead {
+        use super::*;
+        use test_case::test_case;
+
+        #[test_case(0.0, 0.75)]
+        #[test_case(FRAC_PI_2, 0.5)]
+        #[test_case(PI, 0.25)]
+        #[test_case(-FRAC_PI_2, 0.5)]
+        fn test(rotation: f32, expected: f32) {
+            let distance =
+                wall_distance_ahead(na::Point2::new(0.25, 0.5), na::Rotation2::new(rotation));
+
+            assert_relative_eq!(distance, expected, epsilon = 1e-6);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::*;
+
+/// Sums the signals broadcast by every other Creature within `signal_range`,
+/// attenuated linearly from full strength at zero distance to nothing at the range limit
+pub(crate) fn receive_signals(creatures: &[Creature], config: &Config) -> Vec<Vec<f32>> {
+    if config.signal_range <= 0.0 {
+        return vec![vec![0.0; config.signal_channels]; creatures.len()];
+    }
+
+    let positions: Vec<_> = creatures.iter().map(|creature| creature.position).collect();
+    let grid = SpatialGrid::new(&positions, config.signal_range);
+
+    (0..creatures.len())
+        .map(|idx| {
+            let senders = grid.query(positions[idx], config.signal_range);
+            receive(creatures, idx, senders, config)
+        })
+        .collect()
+}
+
+/// Signals received by the Creature at `idx` alone
+pub(crate) fn receive_signal(creatures: &[Creature], idx: usize, config: &Config) -> Vec<f32> {
+    receive(creatures, idx, 0..creatures.len(), config)
+}
+
+fn receive(
+    creatures: &[Creature],
+    idx: usize,
+    senders: impl Iterator<Item = usize>,
+    config: &Config,
+) -> Vec<f32> {
+    let receiver = &creatures[idx];
+    let mut received = vec![0.0; config.signal_channels];
+
+    for sender_idx in senders {
+        if sender_idx == idx {
+            continue;
+        }
+
+        let sender = &creatures[sender_idx];
+        let distance = na::distance(&receiver.position, &sender.position);
+        if distance >= config.signal_range {
+            continue;
+        }
+
+        let attenuation = 1.0 - distance / config.signal_range;
+        for (channel, value) in received.iter_mut().zip(&sender.signal) {
+            *channel += value * attenuation;
+        }
+    }
+
+    received
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn creature(rng: &mut dyn RngCore, config: &Config, x: f32, signal: Vec<f32>) -> Creature {
+        let mut creature = Creature::random(rng, config);
+        creature.position = na::Point2::new(x, 0.5);
+        creature.signal = signal;
+        creature
+    }
+
+    mod receive_signals {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let config = Config {
+                signal_channels: 2,
+                signal_range: 0.2,
+                ..Default::default()
+            };
+
+            let creatures = vec![
+                creature(&mut rng, &config, 0.5, vec![1.0, -1.0]),
+                creature(&mut rng, &config, 0.55, vec![0.5, 0.5]),
+                creature(&mut rng, &config, 0.65, vec![-1.0, 0.0]),
+                creature(&mut rng, &config, 0.9, vec![1.0, 1.0]),
+            ];
+
+            let received = receive_signals(&creatures, &config);
+
+            assert_relative_eq!(
+                received[0].as_slice(),
+                [0.125, 0.375].as_ref(),
+                epsilon = 1e-6
+            );
+            assert_relative_eq!(
+                received[1].as_slice(),
+                [0.25, -0.75].as_ref(),
+                epsilon = 1e-6
+            );
+            assert_relative_eq!(received[2].as_slice(), [0.5, 0.0].as_ref(), epsilon = 1e-6);
+            assert_relative_eq!(received[3].as_slice(), [0.0, 0.0].as_ref(), epsilon = 1e-6);
+
+            for (idx, expected) in received.iter().enumerate() {
+                let actual = receive_signal(&creatures, idx, &config);
+                assert_relative_eq!(actual.as_slice(), expected.as_slice(), epsilon = 1e-6);
+            }
+        }
+
+        #[test]
+        fn test_matches_brute_force() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let config = Config {
+                signal_channels: 2,
+                signal_range: 0.1,
+                ..Default::default()
+            };
+
+            let creatures: Vec<_> = (0..200)
+                .map(|_| {
+                    let mut creature = Creature::random(&mut rng, &config);
+                    creature.signal = vec![rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0)];
+                    creature
+                })
+                .collect();
+
+            for (idx, received) in receive_signals(&creatures, &config).iter().enumerate() {
+                let expected = receive(&creatures, idx, 0..creatures.len(), &config);
+                assert_relative_eq!(received.as_slice(), expected.as_slice(), epsilon = 1e-5);
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::*;
+
+/// Uniform grid bucketing point indices by cell, for fast neighbourhood queries
+#[derive(Debug, Clone)]
+pub(crate) struct SpatialGrid {
+    cell_size: f32,
+    resolution: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl SpatialGrid {
+    pub(crate) fn new(positions: &[na::Point2<f32>], cell_size: f32) -> SpatialGrid {
+        assert!(cell_size > 0.0);
+
+        let extent = (UPPER_BOUND_X - LOWER_BOUND_X).max(UPPER_BOUND_Y - LOWER_BOUND_Y);
+        let resolution = ((extent / cell_size).ceil() as usize).clamp(1, 1024);
+        let cell_size = extent / resolution as f32;
+
+        let mut grid = SpatialGrid {
+            cell_size,
+            resolution,
+            cells: vec![Vec::new(); resolution * resolution],
+        };
+
+        for (idx, position) in positions.iter().enumerate() {
+            let (x, y) = grid.cell(*position);
+            grid.cells[y * resolution + x].push(idx);
+        }
+
+        grid
+    }
+
+    /// Returns the indices of every point in a cell overlapping the square
+    /// of half-width `radius` around `position`; callers filter by exact distance
+    pub(crate) fn query(
+        &self,
+        position: na::Point2<f32>,
+        radius: f32,
+    ) -> impl Iterator<Item = usize> + '_ {
+        let (min_x, min_y) = self.cell(position - na::Vector2::new(radius, radius));
+        let (max_x, max_y) = self.cell(position + na::Vector2::new(radius, radius));
+
+        (min_y..=max_y)
+            .flat_map(move |y| (min_x..=max_x).map(move |x| y * self.resolution + x))
+            .flat_map(move |cell| self.cells[cell].iter().copied())
+    }
+
+    fn cell(&self, position: na::Point2<f32>) -> (usize, usize) {
+        let x = ((position.x - LOWER_BOUND_X) / self.cell_size).max(0.0) as usize;
+        let y = ((position.y - LOWER_BOUND_Y) / self.cell_size).max(0.0) as usize;
+
+        (x.min(self.resolution - 1), y.min(self.resolution - 1))
+    }
+}
+
+/// Row-major index of the cell holding `position` in a square grid of
+/// `resolution` cells per axis spanning the world; positions outside of
+/// it fall into the nearest edge cell
+pub(crate) fn grid_index(position: na::Point2<f32>, resolution: usize) -> usize {
+    let x = ((position.x - LOWER_BOUND_X) / (UPPER_BOUND_X - LOWER_BOUND_X) * resolution as f32)
+        as usize;
+    let y = ((position.y - LOWER_BOUND_Y) / (UPPER_BOUND_Y - LOWER_BOUND_Y) * resolution as f32)
+        as usize;
+
+    y.min(resolution - 1) * resolution + x.min(resolution - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    mod query {
+        use super::*;
+        use test_case::test_case;
+
+        #[test_case(0.01)]
+        #[test_case(0.05)]
+        #[test_case(0.3)]
+        #[test_case(2.0)]
+        fn test_matches_brute_force(radius: f32) {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let positions: Vec<na::Point2<f32>> = (0..500).map(|_| rng.gen()).collect();
+            let grid = SpatialGrid::new(&positions, 0.05);
+
+            for position in positions.iter().take(50) {
+                let mut actual: Vec<_> = grid
+                    .query(*position, radius)
+                    .filter(|idx| na::distance(position, &positions[*idx]) < radius)
+                    .collect();
+                actual.sort_unstable();
+
+                let expected: Vec<_> = (0..positions.len())
+                    .filter(|idx| na::distance(position, &positions[*idx]) < radius)
+                    .collect();
+
+                assert_eq!(actual, expected);
+            }
+        }
+
+        #[test]
+        fn test_out_of_bounds_positions() {
+            let positions = vec![na::Point2::new(-0.5, 0.0), na::Point2::new(1.5, 1.0)];
+            let grid = SpatialGrid::new(&positions, 0.1);
+
+            let found: Vec<_> = grid.query(na::Point2::new(0.0, 0.0), 0.01).collect();
+            assert_eq!(found, vec![0]);
+
+            let found: Vec<_> = grid.query(na::Point2::new(1.0, 1.0), 0.01).collect();
+            assert_eq!(found, vec![1]);
+        }
+    }
+}
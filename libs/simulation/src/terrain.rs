@@ -0,0 +1,217 @@
+use crate::*;
+
+const NOISE_NODES: usize = 5; // Control points per axis for the terrain noise
+const MAX_FRICTION: f32 = 0.9; // Upper bound for terrain friction
+const SPAWN_ATTEMPTS: usize = 32; // Rejection sampling attempts when placing food
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerrainCell {
+    pub(crate) friction: f32,    // Fraction of movement lost [0, 1)
+    pub(crate) temperature: f32, // Ambient temperature [0, 1], 0.5 is neutral
+    pub(crate) fertility: f32,   // Relative food spawning density [0, 1]
+}
+
+impl TerrainCell {
+    pub fn friction(&self) -> f32 {
+        self.friction
+    }
+
+    pub fn temperature(&self) -> f32 {
+        self.temperature
+    }
+
+    pub fn fertility(&self) -> f32 {
+        self.fertility
+    }
+
+    /// Multiplier applied to metabolic costs, growing away from neutral temperature
+    pub(crate) fn metabolism_factor(&self, config: &Config) -> f32 {
+        1.0 + config.terrain_temperature_cost * (2.0 * (self.temperature - 0.5).abs())
+    }
+}
+
+impl Default for TerrainCell {
+    fn default() -> TerrainCell {
+        TerrainCell {
+            friction: 0.0,
+            temperature: 0.5,
+            fertility: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Terrain {
+    resolution: usize,
+    cells: Vec<TerrainCell>,
+}
+
+impl Terrain {
+    pub fn flat(resolution: usize) -> Terrain {
+        assert!(resolution > 0);
+
+        Terrain {
+            resolution,
+            cells: vec![TerrainCell::default(); resolution * resolution],
+        }
+    }
+
+    pub fn random(rng: &mut dyn RngCore, config: &Config) -> Terrain {
+        let resolution = config.terrain_resolution.max(1);
+        let variation = config.terrain_variation.clamp(0.0, 1.0);
+
+        let friction = noise(rng, resolution);
+        let temperature = noise(rng, resolution);
+        let fertility = noise(rng, resolution);
+
+        let cells = (0..resolution * resolution)
+            .map(|idx| TerrainCell {
+                friction: MAX_FRICTION * variation * friction[idx],
+                temperature: 0.5 + variation * (temperature[idx] - 0.5),
+                fertility: 1.0 - variation * fertility[idx],
+            })
+            .collect();
+
+        Terrain { resolution, cells }
+    }
+
+    pub fn resolution(&self) -> usize {
+        self.resolution
+    }
+
+    pub fn cells(&self) -> &Vec<TerrainCell> {
+        &self.cells
+    }
+
+    /// Returns the cell underneath the given world position
+    pub fn sample(&self, position: na::Point2<f32>) -> TerrainCell {
+        self.cells[grid_index(position, self.resolution)]
+    }
+
+    /// Picks a random position, favouring fertile cells
+    pub(crate) fn random_position(&self, rng: &mut dyn RngCore) -> na::Point2<f32> {
+        for _ in 0..SPAWN_ATTEMPTS {
+            let position = rng.gen();
+
+            if rng.gen::<f32>() < self.sample(position).fertility {
+                return position;
+            }
+        }
+
+        rng.gen()
+    }
+}
+
+/// Smooth value noise in [0, 1], bilinearly interpolated from a coarse random grid
+fn noise(rng: &mut dyn RngCore, resolution: usize) -> Vec<f32> {
+    let nodes: Vec<f32> = (0..NOISE_NODES * NOISE_NODES).map(|_| rng.gen()).collect();
+    let scale = (NOISE_NODES - 1) as f32 / resolution as f32;
+
+    (0..resolution * resolution)
+        .map(|idx| {
+            let x = ((idx % resolution) as f32 + 0.5) * scale;
+            let y = ((idx / resolution) as f32 + 0.5) * scale;
+            let (x0, y0) = (x as usize, y as usize);
+            let (x1, y1) = ((x0 + 1).min(NOISE_NODES - 1), (y0 + 1).min(NOISE_NODES - 1));
+            let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+
+            let top = nodes[y0 * NOISE_NODES + x0] * (1.0 - tx) + nodes[y0 * NOISE_NODES + x1] * tx;
+            let bottom =
+                nodes[y1 * NOISE_NODES + x0] * (1.0 - tx) + nodes[y1 * NOISE_NODES + x1] * tx;
+
+            top * (1.0 - ty) + bottom * ty
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn config(variation: f32) -> Config {
+        Config {
+            terrain_resolution: 8,
+            terrain_variation: variation,
+            ..Default::default()
+        }
+    }
+
+    mod random {
+        use super::*;
+
+        #[test]
+        fn test_zero_variation_is_flat() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let terrain = Terrain::random(&mut rng, &config(0.0));
+
+            assert_eq!(terrain.cells().len(), 64);
+            assert!(terrain
+                .cells()
+                .iter()
+                .all(|cell| *cell == TerrainCell::default()));
+        }
+
+        #[test]
+        fn test_bounds() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let terrain = Terrain::random(&mut rng, &config(1.0));
+
+            for cell in terrain.cells() {
+                assert!((0.0..MAX_FRICTION + f32::EPSILON).contains(&cell.friction));
+                assert!((0.0..=1.0).contains(&cell.temperature));
+                assert!((0.0..=1.0).contains(&cell.fertility));
+            }
+        }
+
+        #[test]
+        fn test_variation_is_clamped() {
+            let terrain =
+                Terrain::random(&mut ChaCha8Rng::from_seed(Default::default()), &config(1.0));
+            let clamped =
+                Terrain::random(&mut ChaCha8Rng::from_seed(Default::default()), &config(5.0));
+
+            assert_eq!(clamped.cells(), terrain.cells());
+        }
+    }
+
+    mod sample {
+        use super::*;
+        use test_case::test_case;
+
+        #[test_case(0.0, 0.0, 0)]
+        #[test_case(0.49, 0.0, 0)]
+        #[test_case(0.5, 0.0, 1)]
+        #[test_case(1.0, 0.0, 1)]
+        #[test_case(0.0, 0.5, 2)]
+        #[test_case(1.0, 1.0, 3)]
+        fn test(x: f32, y: f32, expected_idx: usize) {
+            let mut terrain = Terrain::flat(2);
+            for (idx, cell) in terrain.cells.iter_mut().enumerate() {
+                cell.fertility = idx as f32;
+            }
+
+            let cell = terrain.sample(na::Point2::new(x, y));
+
+            assert_eq!(cell.fertility, expected_idx as f32);
+        }
+    }
+
+    mod random_position {
+        use super::*;
+
+        #[test]
+        fn test_avoids_barren_cells() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut terrain = Terrain::flat(2);
+            terrain.cells[0].fertility = 0.0;
+            terrain.cells[1].fertility = 0.0;
+
+            for _ in 0..100 {
+                let position = terrain.random_position(&mut rng);
+                assert!(position.y >= 0.5);
+            }
+        }
+    }
+}
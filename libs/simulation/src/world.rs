@@ -5,12 +5,15 @@ pub struct World {
     pub(crate) creatures: Vec<Creature>,
     pub(crate) foods: Vec<Food>,
     pub(crate) food_count: usize,
+    pub(crate) terrain: Terrain,
+    pub(crate) pheromones: PheromoneField,
 }
 
 impl World {
     pub fn random(rng: &mut dyn RngCore, config: &Config) -> World {
+        let terrain = Terrain::random(rng, config);
         let creatures: Vec<Creature> = (0..config.population_count)
-            .map(|_| Creature::random(rng, &config))
+            .map(|_| Creature::random(rng, config))
             .collect();
 
         let k = ((config.target_population as f32 - creatures.len() as f32)
@@ -20,12 +23,16 @@ impl World {
             (k * config.food_count as f32
                 * (config.target_population as f32 / creatures.len() as f32)) as usize;
         let food_count = food_count.clamp(0, config.food_count * 3);
-        let foods = (0..food_count).map(|_| Food::random(rng)).collect();
+        let foods = (0..food_count)
+            .map(|_| Food::on_terrain(rng, &terrain))
+            .collect();
 
         World {
             creatures,
             foods,
-            food_count: food_count as usize,
+            food_count,
+            terrain,
+            pheromones: PheromoneField::new(config.pheromone_resolution, config.pheromone_channels),
         }
     }
 
@@ -37,6 +44,14 @@ impl World {
         &self.foods
     }
 
+    pub fn terrain(&self) -> &Terrain {
+        &self.terrain
+    }
+
+    pub fn pheromones(&self) -> &PheromoneField {
+        &self.pheromones
+    }
+
     pub fn update_food(&mut self, config: &Config, rng: &mut dyn RngCore) {
         let k = ((config.target_population as f32 - self.creatures.len() as f32)
             / (config.target_population as f32 * 0.5))
@@ -48,8 +63,10 @@ impl World {
         self.food_count = self.food_count.clamp(0, config.food_count * 3);
 
         if self.foods.len() < self.food_count {
-            self.foods
-                .extend((0..self.food_count - self.foods.len()).map(|_| Food::random(rng)));
+            let terrain = &self.terrain;
+            self.foods.extend(
+                (0..self.food_count - self.foods.len()).map(|_| Food::on_terrain(rng, terrain)),
+            );
         } else if self.foods.len() > self.food_count {
             self.foods.truncate(self.food_count);
         }
@@ -1,4 +1,6 @@
 use lib_simulation as sim;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
 
 #[test]
 fn test_simulation() {
@@ -11,8 +13,384 @@ fn test_simulation() {
         if i % 100 == 0 {
             println!("Step {} Pop = {}", i, simulation.world().creatures().len());
         }
-        if simulation.world().creatures().len() == 0 {
+        if simulation.world().creatures().is_empty() {
             break;
         }
     }
 }
+
+#[test]
+fn test_brain_trace() {
+    let mut rng = rand::thread_rng();
+    let mut simulation = sim::Simulation::random(&mut rng, None);
+    simulation.step(&mut rng);
+
+    let trace = simulation.brain_trace(0).unwrap();
+    let lengths: Vec<_> = trace.iter().map(Vec::len).collect();
+    assert_eq!(lengths[1..], [18, 2]);
+    assert!(simulation.brain_trace(usize::MAX).is_none());
+}
+
+#[test]
+fn test_diversity_leaves_evolution_alone() {
+    let run = |diversity_interval| {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let config = sim::Config {
+            diversity_interval,
+            ..Default::default()
+        };
+
+        let mut simulation = sim::Simulation::random(&mut rng, Some(config));
+        for _ in 0..200 {
+            simulation.step(&mut rng);
+        }
+
+        let positions: Vec<_> = simulation
+            .world()
+            .creatures()
+            .iter()
+            .map(|creature| creature.position())
+            .collect();
+        (positions, *simulation.diversity())
+    };
+
+    let (measured, diversity) = run(1);
+    let (unmeasured, _) = run(0);
+
+    assert_eq!(measured, unmeasured);
+    assert!(diversity.genetic() > 0.0);
+}
+
+#[test]
+fn test_pretrain() {
+    let mut rng = rand::thread_rng();
+    let trainer = sim::Trainer::new(
+        sim::Loss::MeanSquaredError,
+        sim::Optimizer::adam(0.01),
+        5,
+        4,
+    );
+
+    let mut simulation = sim::Simulation::random(&mut rng, None);
+    let inputs = simulation.brain_trace(0).unwrap()[0].len();
+    let samples = vec![sim::Sample::new(vec![0.5; inputs], vec![0.5, 0.0]); 8];
+    assert!(simulation.pretrain(&mut rng, &samples, &trainer).is_some());
+
+    // Samples that don't fit the brains are rejected rather than trained on
+    assert!(simulation.pretrain(&mut rng, &[], &trainer).is_none());
+    let mismatched = vec![sim::Sample::new(vec![0.5; inputs + 1], vec![0.5, 0.0]); 8];
+    assert!(simulation
+        .pretrain(&mut rng, &mismatched, &trainer)
+        .is_none());
+    let mismatched = vec![sim::Sample::new(vec![0.5; inputs], vec![0.5]); 8];
+    assert!(simulation
+        .pretrain(&mut rng, &mismatched, &trainer)
+        .is_none());
+
+    // Brains that can't be trained are left alone instead
+    let config = sim::Config {
+        brain_kind: sim::BrainKind::Neat,
+        ..Default::default()
+    };
+    let mut simulation = sim::Simulation::random(&mut rng, Some(config));
+    assert!(simulation.pretrain(&mut rng, &samples, &trainer).is_none());
+    simulation.step(&mut rng);
+}
+
+#[test]
+fn test_simulation_with_pheromones() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let config = sim::Config {
+        pheromone_channels: 2,
+        ..Default::default()
+    };
+
+    let mut simulation = sim::Simulation::random(&mut rng, Some(config));
+    simulation.step(&mut rng);
+
+    let pheromones = simulation.world().pheromones();
+    assert_eq!(pheromones.channels(), 2);
+
+    // Creatures leave a trail where they have just been
+    assert!(simulation
+        .world()
+        .creatures()
+        .iter()
+        .any(|creature| pheromones
+            .sample(creature.position())
+            .iter()
+            .any(|value| *value > 0.0)));
+
+    for _ in 0..1000 {
+        simulation.step(&mut rng);
+        if simulation.world().creatures().is_empty() {
+            break;
+        }
+    }
+}
+
+#[test]
+fn test_simulation_with_all_senses() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let inputs = |simulation: &sim::Simulation| simulation.brain_trace(0).unwrap()[0].len();
+    let default = inputs(&sim::Simulation::random(&mut rng, None));
+
+    let config = sim::Config {
+        sense_energy: true,
+        sense_speed: true,
+        sense_age: true,
+        sense_walls: true,
+        sense_clock: true,
+        ..Default::default()
+    };
+
+    let mut simulation = sim::Simulation::random(&mut rng, Some(config));
+
+    // Energy, speed and age, then two inputs each for the walls and the clock
+    assert_eq!(inputs(&simulation), default + 7);
+
+    for _ in 0..1000 {
+        simulation.step(&mut rng);
+        if simulation.world().creatures().is_empty() {
+            break;
+        }
+    }
+}
+
+#[test]
+fn test_simulation_with_collisions() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let config = sim::Config {
+        creature_collisions: true,
+        ..Default::default()
+    };
+
+    let mut simulation = sim::Simulation::random(&mut rng, Some(config));
+    for _ in 0..1000 {
+        simulation.step(&mut rng);
+        if simulation.world().creatures().is_empty() {
+            break;
+        }
+
+        // Overlapping creatures are pushed apart, up to what a single pass leaves in crowds
+        let creatures = simulation.world().creatures();
+        for (idx, a) in creatures.iter().enumerate() {
+            for b in &creatures[idx + 1..] {
+                let reach = (a.size() + b.size()) / 2.0;
+                let distance = (a.position() - b.position()).norm();
+                assert!(distance >= 0.75 * reach, "{} < {}", distance, reach);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_simulation_with_signals() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let config = sim::Config {
+        signal_channels: 3,
+        signal_color: true,
+        ..Default::default()
+    };
+
+    let mut simulation = sim::Simulation::random(&mut rng, Some(config));
+    simulation.step(&mut rng);
+
+    // Brains broadcast through every channel, and the first one tints the creature
+    let creatures = simulation.world().creatures();
+    assert!(creatures
+        .iter()
+        .any(|creature| creature.signal().iter().any(|value| *value != 0.0)));
+
+    for creature in creatures {
+        assert_eq!(creature.signal().len(), 3);
+        assert_eq!(creature.color(), (creature.signal()[0] + 1.0) / 2.0);
+    }
+
+    for _ in 0..1000 {
+        simulation.step(&mut rng);
+        if simulation.world().creatures().is_empty() {
+            break;
+        }
+    }
+}
+
+#[test]
+fn test_simulation_with_recurrent_brains() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let config = sim::Config {
+        brain_hidden_layers: vec![
+            sim::LayerTopology {
+                neurons: 8,
+                activation: sim::Activation::Tanh,
+                kind: sim::LayerKind::Gru,
+                initializer: sim::Initializer::Uniform,
+            },
+            sim::LayerTopology {
+                neurons: 6,
+                activation: sim::Activation::Tanh,
+                kind: sim::LayerKind::Elman,
+                initializer: sim::Initializer::Uniform,
+            },
+        ],
+        ..Default::default()
+    };
+
+    let mut simulation = sim::Simulation::random(&mut rng, Some(config));
+    for _ in 0..1000 {
+        simulation.step(&mut rng);
+        if simulation.world().creatures().is_empty() {
+            break;
+        }
+    }
+
+    // Both recurrent layers show up in the trace and stay bounded over time
+    let trace = simulation.brain_trace(0).unwrap();
+    let lengths: Vec<_> = trace.iter().map(Vec::len).collect();
+    assert_eq!(lengths[1..], [8, 6, 2]);
+    assert!(trace[1..].iter().flatten().all(|value| value.abs() <= 1.0));
+}
+
+#[test]
+fn test_simulation_with_neat_brains() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let config = sim::Config {
+        brain_kind: sim::BrainKind::Neat,
+        neat_add_node_rate: 0.2,
+        neat_add_connection_rate: 0.3,
+        ..Default::default()
+    };
+
+    let mut simulation = sim::Simulation::random(&mut rng, Some(config));
+    for _ in 0..3000 {
+        simulation.step(&mut rng);
+        if simulation.world().creatures().is_empty() {
+            break;
+        }
+    }
+
+    // Offspring grow hidden nodes on top of the minimal genomes
+    let creatures = simulation.world().creatures().len();
+    assert!((0..creatures).any(|idx| !simulation.brain_trace(idx).unwrap()[1].is_empty()));
+}
+
+#[test]
+fn test_simulation_with_ctrnn_brains() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let config = sim::Config {
+        brain_kind: sim::BrainKind::Ctrnn,
+        ctrnn_neurons: 6,
+        ..Default::default()
+    };
+
+    let mut simulation = sim::Simulation::random(&mut rng, Some(config));
+    for _ in 0..1000 {
+        simulation.step(&mut rng);
+        if simulation.world().creatures().is_empty() {
+            break;
+        }
+    }
+
+    // Neurons past the outputs make up the hidden layer
+    let trace = simulation.brain_trace(0).unwrap();
+    let lengths: Vec<_> = trace.iter().map(Vec::len).collect();
+    assert_eq!(lengths[1..], [4, 2]);
+    assert!(trace.iter().flatten().all(|value| value.is_finite()));
+}
+
+#[test]
+fn test_simulation_with_compressed_brains() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+    for brain_compression in [
+        sim::BrainCompression::Pruned,
+        sim::BrainCompression::Quantized,
+    ] {
+        let config = sim::Config {
+            brain_compression,
+            ..Default::default()
+        };
+
+        let mut simulation = sim::Simulation::random(&mut rng, Some(config));
+        for _ in 0..1000 {
+            simulation.step(&mut rng);
+            if simulation.world().creatures().is_empty() {
+                break;
+            }
+        }
+
+        // Compressed brains still report every layer they go through
+        let trace = simulation.brain_trace(0).unwrap();
+        let lengths: Vec<_> = trace.iter().map(Vec::len).collect();
+        assert_eq!(lengths[1..], [18, 2]);
+        assert!(trace.iter().flatten().all(|value| value.is_finite()));
+    }
+}
+
+#[test]
+fn test_simulation_with_novelty_search() {
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+    let config = sim::Config {
+        novelty_weight: 0.5,
+        ..Default::default()
+    };
+
+    let mut simulation = sim::Simulation::random(&mut rng, Some(config));
+    for _ in 0..1000 {
+        simulation.step(&mut rng);
+        if simulation.world().creatures().is_empty() {
+            break;
+        }
+    }
+
+    // Creatures are still born, just picked differently
+    assert!(simulation
+        .world()
+        .creatures()
+        .iter()
+        .any(|creature| creature.generation() > 0));
+}
+
+#[test]
+fn test_simulation_with_plastic_brains() {
+    let run = |plasticity_baseline, plasticity_reward| {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let config = sim::Config {
+            brain_plasticity: true,
+            plasticity_baseline,
+            plasticity_reward,
+            ..Default::default()
+        };
+
+        let mut simulation = sim::Simulation::random(&mut rng, Some(config));
+        for _ in 0..1000 {
+            simulation.step(&mut rng);
+            if simulation.world().creatures().is_empty() {
+                break;
+            }
+        }
+        simulation
+    };
+    let positions = |simulation: &sim::Simulation| -> Vec<_> {
+        simulation
+            .world()
+            .creatures()
+            .iter()
+            .map(|creature| creature.position())
+            .collect()
+    };
+
+    let learning = run(0.01, 1.0);
+    let frozen = run(0.0, 0.0);
+
+    let lengths: Vec<_> = learning
+        .brain_trace(0)
+        .unwrap()
+        .iter()
+        .map(Vec::len)
+        .collect();
+    assert_eq!(lengths[1..], [18, 2]);
+
+    // Everything else being equal, learning changes how creatures behave
+    assert_ne!(positions(&learning), positions(&frozen));
+}
@@ -21,6 +21,21 @@ const getStepSize = (value: number): number => {
   return decimals ? 10 ** -(decimals.split('').findIndex((d) => d !== '0') + 1) : 1;
 };
 
+// Brain settings are lists of layer sizes or of names, edited as comma separated text
+const parseOption = (previous: unknown, input: string): unknown => {
+  if (Array.isArray(previous)) {
+    const items = input.split(',').map((item) => item.trim()).filter((item) => item !== '');
+    const isNumeric = previous.length > 0
+      ? typeof previous[0] === 'number'
+      : items.every((item) => !isNaN(Number(item)));
+
+    return isNumeric ? items.map(Number) : items;
+  } else if (typeof previous === 'string') {
+    return input.trim();
+  }
+  return parseFloat(input);
+};
+
 interface ConfigFormProps {
   handleClose(isSimReady?: boolean): void,
   isNestedConfig?: boolean
@@ -70,21 +85,39 @@ export default function ConfigForm({ handleClose, isNestedConfig }: ConfigFormPr
           {config &&
             configStepSizes &&
             configOptions.map((option, i) => {
+              // eslint-disable-next-line @typescript-eslint/no-explicit-any
+              const value = (config as any)[option];
+              const setOption = (input: string): void =>
+                setConfig((prevConfig) => ({
+                  ...prevConfig,
+                  toJSON: prevConfig.toJSON,
+                  free: prevConfig.free,
+                  [option]: parseOption(value, input),
+                }));
+
+              if (typeof value === 'string' || Array.isArray(value)) {
+                const text = Array.isArray(value) ? value.join(', ') : value;
+
+                // Parsed once done editing, so that separators can be typed
+                return (
+                  <label key={`${option}:${text}`}>
+                    <span>{option}:</span>
+                    <input
+                      type="text"
+                      onBlur={(e) => setOption(e.target.value)}
+                      defaultValue={text}
+                    />
+                  </label>
+                );
+              }
+
               return (
                 <label key={option}>
                   <span>{option}:</span>
                   <input
                     type="number"
-                    onChange={(e) =>
-                      setConfig((prevConfig) => ({
-                        ...prevConfig,
-                        toJSON: prevConfig.toJSON,
-                        free: prevConfig.free,
-                        [option]: parseFloat(e.target.value),
-                      }))
-                    }
-                    // eslint-disable-next-line @typescript-eslint/no-explicit-any
-                    value={(config as any)[option]}
+                    onChange={(e) => setOption(e.target.value)}
+                    value={value}
                     min={configStepSizes[i]}
                     step={configStepSizes[i]}
                   />
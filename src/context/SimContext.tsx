@@ -57,8 +57,13 @@ export const SimContextProvider = ({ children }: SimContextProviderProps): JSX.E
   const [startNewSim, setStartNewSim] = useState(false);
 
   const newSim = (): void => {
-    setSimulation(() => {
-      return new Simulation(new Config(simConfig.toJSON()));
+    setSimulation((current) => {
+      try {
+        return new Simulation(new Config(simConfig.toJSON()));
+      } catch (error) {
+        window.alert(`Invalid config: ${(error as Error).message}`);
+        return current;
+      }
     });
     setWorld(simulation.world());
   };